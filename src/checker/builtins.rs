@@ -0,0 +1,153 @@
+use super::types;
+use crate::parser::ast::{Expr, ExprKind, Type};
+
+// signatures of the functions in the `SOURCES` and `SINKS` namespaces; a
+// sink gives the stream that is written to with `->`
+pub struct Builtin {
+    pub namespace: &'static str,
+    pub name: &'static str,
+    pub inputs: Vec<Type>,
+    pub outputs: Vec<Type>,
+}
+
+fn builtins() -> Vec<Builtin> {
+    let bytes = || types::stream(Type::new("u8", vec![]));
+    let builtin = |namespace, name, inputs, outputs| Builtin {
+        namespace,
+        name,
+        inputs,
+        outputs,
+    };
+    vec![
+        builtin("SOURCES", "stdin", vec![], vec![bytes()]),
+        builtin("SOURCES", "args", vec![], vec![types::stream(bytes())]),
+        builtin("SOURCES", "raw_args", vec![], vec![bytes()]),
+        builtin(
+            "SOURCES",
+            "tcp",
+            vec![Type::new("u16", vec![])],
+            vec![bytes(), bytes()],
+        ),
+        builtin(
+            "SOURCES",
+            "file",
+            vec![bytes()],
+            vec![types::bool(), bytes()],
+        ),
+        builtin("SINKS", "stdout", vec![], vec![bytes()]),
+        builtin("SINKS", "stderr", vec![], vec![bytes()]),
+        builtin("SINKS", "file", vec![bytes()], vec![bytes()]),
+    ]
+}
+
+// the signature of a method called with `value.method(args)`
+pub struct Method {
+    pub name: &'static str,
+    pub inputs: Vec<Type>,
+    pub output: Type,
+}
+
+// the methods of `receiver`, where a method is listed once for each number
+// of arguments it takes; `is_some()` and `is_none()` may be called on any
+// value
+pub fn methods(receiver: &Type) -> Vec<Method> {
+    let method = |name, inputs, output| Method {
+        name,
+        inputs,
+        output,
+    };
+    let u32 = || Type::new("u32", vec![]);
+    let mut methods = vec![
+        method("is_some", vec![], types::bool()),
+        method("is_none", vec![], types::bool()),
+    ];
+    if types::is_integer(receiver) && !types::is_unknown(receiver) {
+        methods.push(method(
+            "ascii",
+            vec![],
+            types::stream(Type::new("u8", vec![])),
+        ));
+    }
+    let item = match receiver.name.as_str() {
+        "stream" if receiver.generics.len() == 1 => receiver.generics[0].clone(),
+        _ => return methods,
+    };
+    let stream = || types::stream(item.clone());
+    methods.extend(vec![
+        method("next", vec![], types::option(item.clone())),
+        method("get", vec![], types::option(item.clone())),
+        method("get", vec![u32()], types::option(item.clone())),
+        method("consume", vec![u32()], stream()),
+        method("skip", vec![u32()], stream()),
+        method("len", vec![], u32()),
+        method("eof", vec![], types::bool()),
+        method("cycle", vec![], stream()),
+        method("chain", vec![stream()], stream()),
+        method("starts_with", vec![stream()], types::bool()),
+        method("split", vec![stream()], types::stream(stream())),
+        method("parse", vec![], Type::new("{integer}", vec![])),
+    ]);
+    methods
+}
+
+pub fn find(namespace: &str, name: &str) -> Option<Builtin> {
+    builtins()
+        .into_iter()
+        .find(|b| b.namespace == namespace && b.name == name)
+}
+
+// whether the call of `path` gives a stream that `value -> path()` writes
+// to, rather than taking the value as its last argument
+pub fn is_sink(path: &Expr) -> bool {
+    match &path.kind {
+        ExprKind::Path { segments } if segments.len() == 2 => {
+            find(&segments[0], &segments[1]).is_some_and(|b| b.namespace == "SINKS")
+        }
+        _ => false,
+    }
+}
+
+// the builtin whose path is closest to `namespace::name`, when it is close
+// enough to be a typo: a third of the name's length, and at least one edit
+pub fn suggest(namespace: &str, name: &str) -> Option<String> {
+    let path = format!("{}::{}", namespace, name);
+    let limit = (name.chars().count() / 3).max(1);
+    builtins()
+        .iter()
+        .map(|b| format!("{}::{}", b.namespace, b.name))
+        .map(|candidate| (distance(&path, &candidate), candidate))
+        .filter(|(d, _)| *d <= limit)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, candidate)| candidate)
+}
+
+// the number of characters to insert, remove or replace to turn `a` into `b`
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let replace = diagonal + (ca != *cb) as usize;
+            diagonal = row[j + 1];
+            row[j + 1] = replace.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[test]
+fn suggestions() {
+    assert_eq!(distance("stdin", "stdout"), 3);
+    assert_eq!(distance("", "abc"), 3);
+    assert_eq!(
+        suggest("SOURCES", "stdn"),
+        Some("SOURCES::stdin".to_string())
+    );
+    assert_eq!(suggest("SOURCE", "tcp"), Some("SOURCES::tcp".to_string()));
+    assert_eq!(suggest("SINKS", "stdot"), Some("SINKS::stdout".to_string()));
+    assert_eq!(suggest("SINKS", "stdin"), None);
+    assert_eq!(suggest("SOURCES", "foo"), None);
+    assert_eq!(suggest("SINKS", "network"), None);
+}
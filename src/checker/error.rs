@@ -0,0 +1,258 @@
+use crate::parser::ast::{IntSuffix, Type};
+use crate::span::Span;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CheckError {
+    FnRedeclared {
+        name: String,
+        span: Span,
+        first: Span,
+    },
+    ParamRedeclared {
+        name: String,
+        span: Span,
+        first: Span,
+    },
+    MainNotFound,
+    UndefinedName {
+        name: String,
+        span: Span,
+    },
+    VarRedeclared {
+        name: String,
+        span: Span,
+        first: Span,
+    },
+    FnNotFound {
+        name: String,
+        span: Span,
+    },
+    // a call into `SOURCES` or `SINKS` that is not one of theirs
+    BuiltinNotFound {
+        path: String,
+        suggestion: Option<String>,
+        span: Span,
+    },
+    // a method that values of the receiver's type do not have
+    MethodNotFound {
+        method: String,
+        receiver: Type,
+        span: Span,
+    },
+    // `break` or `continue` that is not inside a loop
+    OutsideLoop {
+        keyword: &'static str,
+        span: Span,
+    },
+    // `value -> SINKS::stdout()` used as a value rather than a statement
+    WriteNotStatement {
+        span: Span,
+    },
+    UnknownType {
+        name: String,
+        span: Span,
+    },
+    WrongGenericCount {
+        name: String,
+        expected: usize,
+        found: usize,
+        span: Span,
+    },
+    LiteralOutOfRange {
+        suffix: IntSuffix,
+        span: Span,
+    },
+    TypeMismatch {
+        expected: Type,
+        found: Type,
+        span: Span,
+    },
+    ReturnCount {
+        expected: usize,
+        found: usize,
+        span: Span,
+    },
+    // `values -> (a, b)` where the number of values and streams differ
+    FanOutCount {
+        streams: usize,
+        found: usize,
+        span: Span,
+    },
+    ArgCount {
+        name: String,
+        expected: usize,
+        found: usize,
+        span: Span,
+    },
+    // `a, b := value` where the number of names and values differ
+    DestructureCount {
+        names: usize,
+        found: usize,
+        span: Span,
+    },
+    // several values given to a single variable
+    TupleAssigned {
+        found: usize,
+        span: Span,
+    },
+    // a postfix operator other than `?`, which is parsed but has no meaning
+    PostfixUnsupported {
+        op: String,
+        span: Span,
+    },
+    // an option used as the value it holds, which needs `?` to unwrap it
+    OptionNotUnwrapped {
+        found: Type,
+        span: Span,
+    },
+    // a `_` argument that no value written with `->` takes the place of
+    UnfilledPlaceholder {
+        span: Span,
+    },
+}
+
+impl CheckError {
+    // where the error is reported; `None` for errors about the whole file
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            CheckError::FnRedeclared { span, .. }
+            | CheckError::ParamRedeclared { span, .. }
+            | CheckError::UndefinedName { span, .. }
+            | CheckError::VarRedeclared { span, .. }
+            | CheckError::FnNotFound { span, .. }
+            | CheckError::BuiltinNotFound { span, .. }
+            | CheckError::MethodNotFound { span, .. }
+            | CheckError::OutsideLoop { span, .. }
+            | CheckError::WriteNotStatement { span }
+            | CheckError::UnknownType { span, .. }
+            | CheckError::WrongGenericCount { span, .. }
+            | CheckError::LiteralOutOfRange { span, .. }
+            | CheckError::TypeMismatch { span, .. }
+            | CheckError::ReturnCount { span, .. }
+            | CheckError::FanOutCount { span, .. }
+            | CheckError::ArgCount { span, .. }
+            | CheckError::DestructureCount { span, .. }
+            | CheckError::TupleAssigned { span, .. }
+            | CheckError::PostfixUnsupported { span, .. }
+            | CheckError::OptionNotUnwrapped { span, .. }
+            | CheckError::UnfilledPlaceholder { span } => Some(span.clone()),
+            CheckError::MainNotFound => None,
+        }
+    }
+}
+
+impl Display for CheckError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            CheckError::FnRedeclared { name, .. } => {
+                write!(f, "redeclaration of function name `{}`", name)
+            }
+            CheckError::ParamRedeclared { name, .. } => {
+                write!(f, "redeclaration of parameter name `{}`", name)
+            }
+            CheckError::MainNotFound => write!(f, "`main` function not found"),
+            CheckError::UndefinedName { name, .. } => {
+                write!(f, "cannot find `{}` in this scope", name)
+            }
+            CheckError::VarRedeclared { name, .. } => {
+                write!(f, "redeclaration of variable name `{}`", name)
+            }
+            CheckError::FnNotFound { name, .. } => {
+                write!(f, "cannot find function `{}`", name)
+            }
+            CheckError::BuiltinNotFound { path, .. } => write!(f, "cannot find `{}`", path),
+            CheckError::MethodNotFound {
+                method, receiver, ..
+            } => write!(f, "no method `{}` for `{}`", method, receiver),
+            CheckError::OutsideLoop { keyword, .. } => {
+                write!(f, "`{}` outside of a loop", keyword)
+            }
+            CheckError::WriteNotStatement { .. } => {
+                write!(f, "a write to a stream must be a statement of its own")
+            }
+            CheckError::UnknownType { name, .. } => write!(f, "unrecognized type `{}`", name),
+            CheckError::WrongGenericCount {
+                name,
+                expected,
+                found,
+                ..
+            } => write!(
+                f,
+                "expected {} generic{} for `{}` type, found {}",
+                expected,
+                if *expected == 1 { "" } else { "s" },
+                name,
+                found
+            ),
+            CheckError::LiteralOutOfRange { suffix, .. } => write!(
+                f,
+                "literal out of range for `{}`, the range is `{}`",
+                suffix.name(),
+                suffix.range()
+            ),
+            CheckError::TypeMismatch {
+                expected, found, ..
+            } => write!(
+                f,
+                "mismatched types: expected `{}`, found `{}`",
+                expected, found
+            ),
+            CheckError::ReturnCount {
+                expected, found, ..
+            } => write!(
+                f,
+                "expected {} return value{}, found {}",
+                expected,
+                if *expected == 1 { "" } else { "s" },
+                found
+            ),
+            CheckError::FanOutCount { streams, found, .. } => write!(
+                f,
+                "expected {} values to write to {} streams, found {}",
+                streams, streams, found
+            ),
+            CheckError::ArgCount {
+                name,
+                expected,
+                found,
+                ..
+            } => write!(
+                f,
+                "`{}` takes {} argument{}, found {}",
+                name,
+                expected,
+                if *expected == 1 { "" } else { "s" },
+                found
+            ),
+            CheckError::DestructureCount { names, found, .. } => write!(
+                f,
+                "cannot assign {} value{} to {} name{}",
+                found,
+                if *found == 1 { "" } else { "s" },
+                names,
+                if *names == 1 { "" } else { "s" }
+            ),
+            CheckError::TupleAssigned { found, .. } => write!(
+                f,
+                "cannot assign {} values to a single variable, as there is no tuple type",
+                found
+            ),
+            CheckError::PostfixUnsupported { op, .. } => {
+                write!(f, "postfix `{}` is not supported", op)
+            }
+            CheckError::OptionNotUnwrapped { found, .. } => {
+                write!(
+                    f,
+                    "`{}` must be unwrapped with `?` before its value is used",
+                    found
+                )
+            }
+            CheckError::UnfilledPlaceholder { .. } => {
+                write!(f, "`_` is not replaced by a value written with `->`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CheckError {}
@@ -1,5 +1,15 @@
+pub(crate) mod builtins;
+mod error;
+mod scope;
+mod types;
+
+pub use error::CheckError;
+pub use scope::{Symbol, SymbolId};
+
+use crate::lexer::Token;
 use crate::parser::ast;
-use std::collections::{HashMap, HashSet};
+use crate::T;
+use std::collections::HashMap;
 
 // have: list of items
 // need to:
@@ -16,6 +26,16 @@ use std::collections::{HashMap, HashSet};
 pub struct Checker<'a> {
     items: &'a Vec<ast::Item>,
     fn_names: HashMap<String, FnInfo>,
+    // types of the outputs of the function being checked
+    outputs: Vec<ast::Type>,
+    scopes: scope::Scopes,
+    // the type of each expression, found by its span
+    types: HashMap<ast::Span, ast::Type>,
+    // how many loops the statement being checked is in
+    loops: usize,
+    // a program that is only run for its tests needs no `main`
+    needs_main: bool,
+    errors: Vec<CheckError>,
 }
 
 struct FnInfo {
@@ -26,54 +46,117 @@ struct FnInfo {
 impl<'a> Checker<'a> {
     pub fn new(items: &'a Vec<ast::Item>) -> Self {
         Self {
-            items: items,
+            items,
             fn_names: HashMap::new(),
+            outputs: Vec::new(),
+            scopes: scope::Scopes::default(),
+            types: HashMap::new(),
+            loops: 0,
+            needs_main: true,
+            errors: Vec::new(),
         }
     }
-    pub fn check_all(&mut self) {
-        // TODO:
-        // - check type safety
-        let mut set: HashSet<&str> = HashSet::new();
+
+    pub fn for_tests(mut self) -> Self {
+        self.needs_main = false;
+        self
+    }
+    pub fn check_all(&mut self) -> Result<(), Vec<CheckError>> {
+        // signatures are gathered first so that a function may be called
+        // before it is defined
+        let mut declared: HashMap<&str, &ast::Span> = HashMap::new();
         for item in self.items {
-            let ast::Item::Function { name, .. } = item;
-            if set.contains(name.as_str()) {
-                panic!("Redeclaration of function name \"{}\"", name);
+            let (name, parameters, return_params) = match &item.kind {
+                ast::ItemKind::Function {
+                    name,
+                    parameters,
+                    return_params,
+                    ..
+                } => (name, parameters, return_params),
+                ast::ItemKind::Error => continue,
+            };
+            if let Some(first) = declared.get(name.as_str()) {
+                self.errors.push(CheckError::FnRedeclared {
+                    name: name.to_string(),
+                    span: item.span.clone(),
+                    first: (*first).clone(),
+                });
+                continue;
             }
-            set.insert(name);
+            declared.insert(name, &item.span);
+            let types = |params: &[ast::Param]| params.iter().map(|p| p.typ.clone()).collect();
+            self.fn_names.insert(
+                name.to_string(),
+                FnInfo {
+                    input_params: types(parameters),
+                    output_params: types(return_params),
+                },
+            );
+        }
+        for item in self.items {
             self.check(item);
         }
-        if !set.contains("main") {
-            panic!("main function not found");
+        if self.needs_main && !declared.contains_key("main") {
+            self.errors.push(CheckError::MainNotFound);
         }
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(std::mem::take(&mut self.errors))
+        }
+    }
+
+    // the symbol the identifier at `span` refers to, once checked
+    pub fn resolve(&self, span: &ast::Span) -> Option<SymbolId> {
+        self.scopes.resolved(span)
+    }
+
+    pub fn symbol(&self, id: SymbolId) -> &Symbol {
+        self.scopes.symbol(id)
+    }
+
+    // the type of an expression, once checked
+    pub fn type_of(&self, expr: &ast::Expr) -> Option<&ast::Type> {
+        self.types.get(&expr.span)
     }
 
     fn check(&mut self, item: &ast::Item) {
-        match item {
-            ast::Item::Function {
+        match &item.kind {
+            ast::ItemKind::Function {
                 name,
                 parameters,
                 body,
                 return_params,
             } => self.check_fn(name, parameters, body, return_params),
+            ast::ItemKind::Error => {}
         }
     }
 
     fn check_fn(
         &mut self,
-        _name: &String,
-        params: &Vec<(String, ast::Type)>,
-        body: &Vec<ast::Stmt>,
-        return_params: &Vec<(String, ast::Type)>,
+        _name: &str,
+        params: &[ast::Param],
+        body: &[ast::Stmt],
+        return_params: &[ast::Param],
     ) {
-        let mut set = HashSet::new();
-        for (name, param_type) in params.iter().chain(return_params.iter()) {
-            self.check_type(param_type);
-            if set.contains(name) {
-                panic!("Redeclaration of parameter name \"{}\"", name);
+        // the body shares a scope with the parameters and outputs
+        self.scopes.push();
+        for param in params.iter().chain(return_params.iter()) {
+            self.check_type(&param.typ);
+            let declared = self
+                .scopes
+                .declare(&param.name, param.typ.clone(), &param.span);
+            if let Err(first) = declared {
+                self.errors.push(CheckError::ParamRedeclared {
+                    name: param.name.clone(),
+                    span: param.span.clone(),
+                    first,
+                });
             }
-            set.insert(name);
         }
-        self.check_block(body);
+        self.outputs = return_params.iter().map(|p| p.typ.clone()).collect();
+        self.check_stmts(body);
+        self.scopes.pop();
     }
 
     fn check_type(&mut self, typ: &ast::Type) {
@@ -81,37 +164,860 @@ impl<'a> Checker<'a> {
             "bool" | "u8" | "u16" | "u32" | "u64" | "i8" | "i16" | "i32" | "i64" => {
                 self.check_basic_type(typ);
             }
-            // TODO: decide if I want option types
-            // "option" => {
-            //     if typ.generics.len() != 1 {
-            //         panic!("Expected exactly one generic for `option` type");
-            //     }
-            //     for generic in &typ.generics {
-            //         check_type(&generic);
-            //     }
-            // }
-            "stream" => {
+            "option" | "stream" => {
                 if typ.generics.len() != 1 {
-                    panic!("Expected exactly one generic for `stream` type");
+                    self.errors.push(CheckError::WrongGenericCount {
+                        name: typ.name.clone(),
+                        expected: 1,
+                        found: typ.generics.len(),
+                        span: typ.span.clone(),
+                    });
                 }
                 for generic in &typ.generics {
-                    self.check_type(&generic);
+                    self.check_type(generic);
                 }
             }
-            t => panic!("Unrecognized type: {}", t),
+            _ => self.errors.push(CheckError::UnknownType {
+                name: typ.name.clone(),
+                span: typ.span.clone(),
+            }),
         }
     }
 
     fn check_basic_type(&mut self, typ: &ast::Type) {
         match typ.name.as_ref() {
             "bool" | "u8" | "u16" | "u32" | "u64" | "i8" | "i16" | "i32" | "i64" => {
-                if typ.generics.len() != 0 {
-                    panic!("Basic types cannot have generics");
+                if !typ.generics.is_empty() {
+                    self.errors.push(CheckError::WrongGenericCount {
+                        name: typ.name.clone(),
+                        expected: 0,
+                        found: typ.generics.len(),
+                        span: typ.span.clone(),
+                    });
+                }
+            }
+            t => unreachable!("Unrecognized basic type: {}", t),
+        }
+    }
+
+    fn check_block(&mut self, block: &[ast::Stmt]) {
+        self.scopes.push();
+        self.check_stmts(block);
+        self.scopes.pop();
+    }
+
+    fn check_stmts(&mut self, stmts: &[ast::Stmt]) {
+        for stmt in stmts {
+            self.check_stmt(stmt);
+        }
+    }
+
+    // a name declared with `:=` may shadow one of an enclosing scope, but
+    // not one declared in the same scope
+    fn declare(&mut self, name: &str, typ: ast::Type, span: &ast::Span) {
+        if let Err(first) = self.scopes.declare(name, typ, span) {
+            self.errors.push(CheckError::VarRedeclared {
+                name: name.to_string(),
+                span: span.clone(),
+                first,
+            });
+        }
+    }
+
+    // the type of the symbol `name` refers to
+    fn resolve_name(&mut self, name: &str, span: &ast::Span) -> Option<ast::Type> {
+        match self.scopes.resolve(name, span) {
+            Some(id) => Some(self.scopes.symbol(id).typ.clone()),
+            None => {
+                self.errors.push(CheckError::UndefinedName {
+                    name: name.to_string(),
+                    span: span.clone(),
+                });
+                None
+            }
+        }
+    }
+
+    fn check_stmt(&mut self, stmt: &ast::Stmt) {
+        match &stmt.kind {
+            // the value is checked first so that `x := x + 1` refers to an
+            // `x` of an enclosing scope
+            ast::StmtKind::Declaration { var_names, value } => {
+                let typ = self.check_expr(value);
+                let types = self.destructure(var_names.len(), typ, &stmt.span);
+                for (name, typ) in var_names.iter().zip(types) {
+                    if name.name != "_" {
+                        self.declare(&name.name, typ, &name.span);
+                    }
+                }
+            }
+            ast::StmtKind::Assignment { var_names, value } => {
+                let typ = self.check_expr(value);
+                let types = self.destructure(var_names.len(), typ, &stmt.span);
+                for (name, typ) in var_names.iter().zip(types) {
+                    if name.name == "_" {
+                        continue;
+                    }
+                    if let Some(expected) = self.resolve_name(&name.name, &name.span) {
+                        self.expect_accepted(&expected, &typ, &value.span);
+                        self.check_inferred(value, &expected);
+                    }
+                }
+            }
+            ast::StmtKind::CompoundAssignment {
+                var_name,
+                op,
+                value,
+            } => {
+                let found = self.check_expr(value);
+                if let Some(typ) = self.resolve_name(&var_name.name, &var_name.span) {
+                    let lhs = ast::Expr::new(
+                        ast::ExprKind::Ident(var_name.name.clone()),
+                        var_name.span.clone(),
+                    );
+                    self.binary(*op, (&lhs, typ), (value, found));
+                }
+            }
+            ast::StmtKind::Increment { var_name } | ast::StmtKind::Decrement { var_name } => {
+                if let Some(typ) = self.resolve_name(&var_name.name, &var_name.span) {
+                    self.expect_integer(&typ, &var_name.span);
+                }
+            }
+            // `value -> SINKS::stdout()` writes to the stream the sink gives
+            ast::StmtKind::Expr(ast::Expr {
+                kind: ast::ExprKind::Pipe { value, call },
+                ..
+            }) if is_sink_call(call) => {
+                self.check_stream_write(value, call, &stmt.span);
+            }
+            ast::StmtKind::Expr(value) => {
+                self.check_expr(value);
+            }
+            ast::StmtKind::StreamWrite { value, stream } => {
+                self.check_stream_write(value, stream, &stmt.span);
+            }
+            ast::StmtKind::IfStmt {
+                condition,
+                body,
+                else_stmt,
+            } => {
+                self.check_condition(condition);
+                self.check_block(body);
+                if let Some(else_stmt) = else_stmt {
+                    self.check_stmt(else_stmt);
+                }
+            }
+            ast::StmtKind::ReturnStmt { values } => {
+                // `_` leaves the output as it is
+                let found: Vec<ast::Type> = values
+                    .iter()
+                    .map(|v| match v.kind {
+                        ast::ExprKind::Placeholder => types::unknown(),
+                        _ => self.check_expr(v),
+                    })
+                    .collect();
+                // a bare `return` keeps the outputs as they were assigned
+                if !values.is_empty() && values.len() != self.outputs.len() {
+                    self.errors.push(CheckError::ReturnCount {
+                        expected: self.outputs.len(),
+                        found: values.len(),
+                        span: stmt.span.clone(),
+                    });
+                    return;
                 }
+                for (i, (value, found)) in values.iter().zip(found).enumerate() {
+                    let expected = self.outputs[i].clone();
+                    self.expect_accepted(&expected, &found, &value.span);
+                    self.check_inferred(value, &expected);
+                }
+            }
+            ast::StmtKind::ForLoop {
+                var_name,
+                stream,
+                stmts,
+            } => {
+                let typ = self.check_expr(stream);
+                let item = self.item_type(&typ, &stream.span);
+                self.scopes.push();
+                self.declare(&var_name.name, item, &var_name.span);
+                self.check_loop(stmts);
+                self.scopes.pop();
+            }
+            ast::StmtKind::WhileLoop { condition, stmts } => {
+                self.check_condition(condition);
+                self.check_loop(stmts);
+            }
+            ast::StmtKind::Block { stmts } => self.check_block(stmts),
+            ast::StmtKind::Assert { condition, message } => {
+                self.check_condition(condition);
+                if let Some(message) = message {
+                    self.check_expr(message);
+                }
+            }
+            ast::StmtKind::ContinueStmt | ast::StmtKind::BreakStmt if self.loops == 0 => {
+                self.errors.push(CheckError::OutsideLoop {
+                    keyword: match stmt.kind {
+                        ast::StmtKind::BreakStmt => "break",
+                        _ => "continue",
+                    },
+                    span: stmt.span.clone(),
+                })
             }
-            t => panic!("Unrecognized basic type: {}", t),
+            ast::StmtKind::ContinueStmt | ast::StmtKind::BreakStmt | ast::StmtKind::Error => {}
+        }
+    }
+
+    fn check_loop(&mut self, stmts: &[ast::Stmt]) {
+        self.loops += 1;
+        self.check_block(stmts);
+        self.loops -= 1;
+    }
+
+    // the types of the values given to `names` names by a declaration or an
+    // assignment; there is no tuple type, so each output of a call or item
+    // of a tuple needs a name of its own
+    fn destructure(&mut self, names: usize, typ: ast::Type, span: &ast::Span) -> Vec<ast::Type> {
+        let found = match &typ {
+            typ if typ.is_tuple() => typ.generics.len(),
+            typ if types::is_unknown(typ) => return vec![types::unknown(); names],
+            _ => 1,
+        };
+        if found != names {
+            self.errors.push(match names {
+                1 if found > 1 => CheckError::TupleAssigned {
+                    found,
+                    span: span.clone(),
+                },
+                _ => CheckError::DestructureCount {
+                    names,
+                    found,
+                    span: span.clone(),
+                },
+            });
+            return vec![types::unknown(); names];
+        }
+        match typ {
+            typ if typ.is_tuple() => typ.generics,
+            typ => vec![typ],
+        }
+    }
+
+    // conditions of `if`, `while` and `assert` must be `bool`
+    fn check_condition(&mut self, condition: &ast::Expr) {
+        let found = self.check_expr(condition);
+        self.expect(&types::bool(), &found, &condition.span);
+    }
+
+    // reports a mismatch unless `found` fits `expected`, and gives the type
+    // that both fit
+    fn expect(&mut self, expected: &ast::Type, found: &ast::Type, span: &ast::Span) -> ast::Type {
+        match types::unify(expected, found) {
+            Some(typ) => typ,
+            None => self.mismatch(expected.clone(), found.clone(), span),
+        }
+    }
+
+    fn expect_accepted(&mut self, expected: &ast::Type, found: &ast::Type, span: &ast::Span) {
+        if !types::accepts(expected, found) {
+            self.mismatch(expected.clone(), found.clone(), span);
+        }
+    }
+
+    fn expect_integer(&mut self, found: &ast::Type, span: &ast::Span) -> ast::Type {
+        if types::is_integer(found) {
+            found.clone()
+        } else {
+            self.mismatch(integer(), found.clone(), span)
+        }
+    }
+
+    fn mismatch(&mut self, expected: ast::Type, found: ast::Type, span: &ast::Span) -> ast::Type {
+        self.errors.push(CheckError::TypeMismatch {
+            expected,
+            found,
+            span: span.clone(),
+        });
+        types::unknown()
+    }
+
+    // `value -> stream` or `values -> (stream, ..)`
+    fn check_stream_write(&mut self, value: &ast::Expr, stream: &ast::Expr, span: &ast::Span) {
+        let typ = self.check_expr(value);
+        let target = self.check_expr(stream);
+        let streams = match &stream.kind {
+            ast::ExprKind::Tuple(streams) => streams,
+            _ => return self.check_write(&typ, (stream, &target), &value.span),
+        };
+        // a value whose type is not known may be several
+        let values = match typ {
+            typ if typ.is_tuple() => typ.generics,
+            typ if types::is_unknown(&typ) => vec![typ; streams.len()],
+            typ => vec![typ],
+        };
+        if values.len() != streams.len() {
+            self.errors.push(CheckError::FanOutCount {
+                streams: streams.len(),
+                found: values.len(),
+                span: span.clone(),
+            });
+            return;
+        }
+        for (i, (stream, target)) in streams.iter().zip(&target.generics).enumerate() {
+            let span = match &value.kind {
+                ast::ExprKind::Tuple(items) => &items[i].span,
+                _ => &value.span,
+            };
+            self.check_write(&values[i], (stream, target), span);
+        }
+    }
+
+    // an option used where the value it holds is expected
+    fn not_unwrapped(&mut self, found: ast::Type, span: &ast::Span) -> ast::Type {
+        self.errors.push(CheckError::OptionNotUnwrapped {
+            found,
+            span: span.clone(),
+        });
+        types::unknown()
+    }
+
+    // a stream is written either one of its items or a stream of them, whose
+    // items are all written; writing `none` writes nothing
+    fn check_write(
+        &mut self,
+        value: &ast::Type,
+        (stream, target): (&ast::Expr, &ast::Type),
+        span: &ast::Span,
+    ) {
+        let item = self.item_type(target, &stream.span);
+        let data = types::unwrap_option(value);
+        if !types::accepts(&item, data) && !types::accepts(&types::stream(item.clone()), data) {
+            self.mismatch(item, value.clone(), span);
+        }
+    }
+
+    // the item type of a stream, for `for` loops and indexes
+    fn item_type(&mut self, typ: &ast::Type, span: &ast::Span) -> ast::Type {
+        match types::item_type(typ) {
+            Some(item) => item,
+            None => self.mismatch(types::stream(types::unknown()), typ.clone(), span),
         }
     }
 
-    fn check_block(&mut self, block: &Vec<ast::Stmt>) {}
+    // records the type of every expression it checks
+    fn check_expr(&mut self, expr: &ast::Expr) -> ast::Type {
+        let typ = self.infer(expr);
+        self.types.insert(expr.span.clone(), typ.clone());
+        typ
+    }
+
+    fn infer(&mut self, expr: &ast::Expr) -> ast::Type {
+        match &expr.kind {
+            ast::ExprKind::Literal(lit) => {
+                self.check_literal(lit, false, &expr.span);
+                types::literal_type(lit)
+            }
+            ast::ExprKind::Ident(name) => self
+                .resolve_name(name, &expr.span)
+                .unwrap_or_else(types::unknown),
+            // a negated literal is checked as a whole so that `-128i8` fits
+            ast::ExprKind::PrefixOp { op, expr: inner } if *op == T![-] => match &inner.kind {
+                ast::ExprKind::Literal(lit) => {
+                    self.check_literal(lit, true, &expr.span);
+                    let typ = types::literal_type(lit);
+                    self.types.insert(inner.span.clone(), typ.clone());
+                    self.expect_integer(&typ, &inner.span)
+                }
+                _ => {
+                    let typ = self.check_expr(inner);
+                    self.expect_integer(&typ, &inner.span)
+                }
+            },
+            ast::ExprKind::PrefixOp { op, expr } => {
+                let typ = self.check_expr(expr);
+                match op {
+                    T![!] => self.expect(&types::bool(), &typ, &expr.span),
+                    _ => self.expect_integer(&typ, &expr.span),
+                }
+            }
+            // `?` unwraps an option
+            ast::ExprKind::PostfixOp { op: T![?], expr } => {
+                let typ = self.check_expr(expr);
+                match typ.name.as_str() {
+                    "option" if typ.generics.len() == 1 => typ.generics[0].clone(),
+                    "_" => types::unknown(),
+                    _ => self.mismatch(types::option(types::unknown()), typ, &expr.span),
+                }
+            }
+            // `?` is the only postfix operator with a meaning
+            ast::ExprKind::PostfixOp { op, expr: inner } => {
+                self.check_expr(inner);
+                self.errors.push(CheckError::PostfixUnsupported {
+                    op: op.to_string(),
+                    span: inner.span.end..expr.span.end,
+                });
+                types::unknown()
+            }
+            ast::ExprKind::InfixOp { op, lhs, rhs } => {
+                let lhs_type = self.check_expr(lhs);
+                let rhs_type = self.check_expr(rhs);
+                self.binary(*op, (lhs, lhs_type), (rhs, rhs_type))
+            }
+            // the values fill the placeholders of a function call first and
+            // then are added to the end of its arguments, as when desugared
+            ast::ExprKind::Pipe { value, call } => {
+                let typ = self.check_expr(value);
+                let typ = match &call.kind {
+                    ast::ExprKind::FnCall { fn_name, args } => {
+                        let values = match typ {
+                            typ if typ.is_tuple() => Some(typ.generics),
+                            typ if types::is_unknown(&typ) => None,
+                            typ => Some(vec![typ]),
+                        };
+                        let piped = values.map(|values| (values, value.span.clone()));
+                        self.check_call(fn_name, args, piped, &call.span)
+                    }
+                    // a write to a sink, which only a statement may be
+                    ast::ExprKind::PathCall { .. } if is_sink_call(call) => {
+                        self.errors.push(CheckError::WriteNotStatement {
+                            span: expr.span.clone(),
+                        });
+                        return types::unknown();
+                    }
+                    // the value is a single argument of a namespace call
+                    ast::ExprKind::PathCall { path, args } => {
+                        let piped = Some((typ, value.span.clone()));
+                        self.check_builtin(path, args, piped, &call.span)
+                    }
+                    ast::ExprKind::MethodCall {
+                        receiver,
+                        method,
+                        args,
+                    } => {
+                        let piped = Some((typ, value.span.clone()));
+                        self.check_method(receiver, method, args, piped, &call.span)
+                    }
+                    _ => unreachable!("the parser only pipes into calls"),
+                };
+                self.types.insert(call.span.clone(), typ.clone());
+                typ
+            }
+            ast::ExprKind::FnCall { fn_name, args } => self.check_call(
+                fn_name,
+                args,
+                Some((Vec::new(), expr.span.clone())),
+                &expr.span,
+            ),
+            ast::ExprKind::PathCall { path, args } => {
+                self.check_builtin(path, args, None, &expr.span)
+            }
+            ast::ExprKind::MethodCall {
+                receiver,
+                method,
+                args,
+            } => self.check_method(receiver, method, args, None, &expr.span),
+            ast::ExprKind::Tuple(items) => {
+                ast::Type::tuple(items.iter().map(|item| self.check_expr(item)).collect())
+            }
+            ast::ExprKind::Index { expr, index } => {
+                let typ = self.check_expr(expr);
+                let item = self.item_type(&typ, &expr.span);
+                match &index.kind {
+                    ast::ExprKind::Range { start, end } => {
+                        for bound in start.iter().chain(end.iter()) {
+                            self.check_index(bound);
+                        }
+                        let u32 = ast::Type::new("u32", vec![]);
+                        self.types.insert(index.span.clone(), types::stream(u32));
+                        types::stream(item)
+                    }
+                    // like `get(n)`, an index past the end gives `none`
+                    _ => {
+                        self.check_index(index);
+                        types::option(item)
+                    }
+                }
+            }
+            ast::ExprKind::Range { start, end } => {
+                let mut item = integer();
+                for bound in start.iter().chain(end.iter()) {
+                    let typ = self.check_expr(bound);
+                    let typ = self.expect_integer(&typ, &bound.span);
+                    item = self.expect(&item, &typ, &bound.span);
+                }
+                types::stream(item)
+            }
+            // a function of a namespace that is named without calling it
+            ast::ExprKind::Path { segments } => {
+                let (namespace, name) = split_path(segments);
+                if builtins::find(&namespace, name).is_none() {
+                    self.errors.push(CheckError::BuiltinNotFound {
+                        path: expr.to_string(),
+                        suggestion: builtins::suggest(&namespace, name),
+                        span: expr.span.clone(),
+                    });
+                }
+                types::unknown()
+            }
+            // `_` only stands for a value as an argument of a function call
+            // or as a returned value
+            ast::ExprKind::Placeholder => {
+                self.errors.push(CheckError::UnfilledPlaceholder {
+                    span: expr.span.clone(),
+                });
+                types::unknown()
+            }
+        }
+    }
+
+    // operands are given with their types; a mismatch is reported at the
+    // operand that does not fit the other
+    fn binary(
+        &mut self,
+        op: Token,
+        (lhs, lhs_type): (&ast::Expr, ast::Type),
+        (rhs, rhs_type): (&ast::Expr, ast::Type),
+    ) -> ast::Type {
+        match op {
+            T![&&] | T![||] => {
+                self.expect(&types::bool(), &lhs_type, &lhs.span);
+                self.expect(&types::bool(), &rhs_type, &rhs.span);
+                types::bool()
+            }
+            // an option is only compared to `none` or another option
+            T![==] | T![!=] => {
+                match types::unify(&lhs_type, &rhs_type) {
+                    Some(typ) => {
+                        self.check_inferred(lhs, &typ);
+                        self.check_inferred(rhs, &typ);
+                    }
+                    None => match (types::option_item(&lhs_type), types::option_item(&rhs_type)) {
+                        (Some(item), _) if types::unify(item, &rhs_type).is_some() => {
+                            self.not_unwrapped(lhs_type.clone(), &lhs.span);
+                        }
+                        (_, Some(item)) if types::unify(&lhs_type, item).is_some() => {
+                            self.not_unwrapped(rhs_type.clone(), &rhs.span);
+                        }
+                        _ => {
+                            self.mismatch(lhs_type.clone(), rhs_type.clone(), &rhs.span);
+                        }
+                    },
+                }
+                types::bool()
+            }
+            T![&] | T![|] | T![^] if lhs_type.name == "bool" => {
+                self.expect(&types::bool(), &rhs_type, &rhs.span)
+            }
+            T![<<] | T![>>] => {
+                self.expect_integer(&rhs_type, &rhs.span);
+                self.expect_integer(&lhs_type, &lhs.span)
+            }
+            _ => {
+                let typ = match (types::is_integer(&lhs_type), types::is_integer(&rhs_type)) {
+                    (true, true) => {
+                        let typ = self.expect(&lhs_type, &rhs_type, &rhs.span);
+                        self.check_inferred(lhs, &typ);
+                        self.check_inferred(rhs, &typ);
+                        typ
+                    }
+                    (true, false) => self.mismatch(known_integer(&lhs_type), rhs_type, &rhs.span),
+                    (false, true) => self.mismatch(known_integer(&rhs_type), lhs_type, &lhs.span),
+                    (false, false) => self.mismatch(integer(), lhs_type, &lhs.span),
+                };
+                match op {
+                    T![<] | T![<=] | T![>] | T![>=] => types::bool(),
+                    _ => typ,
+                }
+            }
+        }
+    }
+
+    // `piped` holds the types of the values written into the call with `->`
+    // and the span they are reported at, or is `None` when how many values
+    // there are is not known
+    fn check_call(
+        &mut self,
+        fn_name: &str,
+        args: &[ast::Expr],
+        piped: Option<(Vec<ast::Type>, ast::Span)>,
+        span: &ast::Span,
+    ) -> ast::Type {
+        let exprs = args;
+        // an unfilled placeholder has no type
+        let mut args: Vec<(Option<ast::Type>, ast::Span)> = args
+            .iter()
+            .map(|arg| match arg.kind {
+                ast::ExprKind::Placeholder => (None, arg.span.clone()),
+                _ => (Some(self.check_expr(arg)), arg.span.clone()),
+            })
+            .collect();
+        let (inputs, outputs) = match self.fn_names.get(fn_name) {
+            Some(info) => (info.input_params.clone(), info.output_params.clone()),
+            None => {
+                self.errors.push(CheckError::FnNotFound {
+                    name: fn_name.to_string(),
+                    span: span.clone(),
+                });
+                return types::unknown();
+            }
+        };
+        // piped values only fill placeholders, so the written arguments
+        // keep their positions
+        for (param, arg) in inputs.iter().zip(exprs) {
+            self.check_inferred(arg, param);
+        }
+        let output = match outputs.len() {
+            1 => outputs[0].clone(),
+            _ => ast::Type::tuple(outputs),
+        };
+        let (values, values_span) = match piped {
+            Some(piped) => piped,
+            // only the arguments that are written out can be checked
+            None => {
+                for (param, (typ, span)) in inputs.iter().zip(args) {
+                    if let Some(typ) = typ {
+                        self.check_input(param, typ, &span);
+                    }
+                }
+                return output;
+            }
+        };
+        for value in values {
+            match args.iter().position(|(typ, _)| typ.is_none()) {
+                Some(index) => args[index].0 = Some(value),
+                None => args.push((Some(value), values_span.clone())),
+            }
+        }
+        if args.len() != inputs.len() {
+            self.errors.push(CheckError::ArgCount {
+                name: fn_name.to_string(),
+                expected: inputs.len(),
+                found: args.len(),
+                span: span.clone(),
+            });
+            return output;
+        }
+        for (param, (typ, span)) in inputs.iter().zip(args) {
+            match typ {
+                Some(typ) => self.check_input(param, typ, &span),
+                None => self.errors.push(CheckError::UnfilledPlaceholder { span }),
+            }
+        }
+        output
+    }
+
+    // a call into `SOURCES` or `SINKS`, where `piped` is the value written
+    // into it with `->`
+    fn check_builtin(
+        &mut self,
+        path: &ast::Expr,
+        args: &[ast::Expr],
+        piped: Option<(ast::Type, ast::Span)>,
+        span: &ast::Span,
+    ) -> ast::Type {
+        let (namespace, name) = match &path.kind {
+            ast::ExprKind::Path { segments } => split_path(segments),
+            _ => unreachable!("the parser only calls paths"),
+        };
+        let exprs = args;
+        let mut args: Vec<(ast::Type, ast::Span)> = args
+            .iter()
+            .map(|arg| (self.check_expr(arg), arg.span.clone()))
+            .collect();
+        args.extend(piped);
+        let builtin = match builtins::find(&namespace, name) {
+            Some(builtin) => builtin,
+            None => {
+                self.errors.push(CheckError::BuiltinNotFound {
+                    path: format!("{}::{}", namespace, name),
+                    suggestion: builtins::suggest(&namespace, name),
+                    span: span.clone(),
+                });
+                return types::unknown();
+            }
+        };
+        let path = format!("{}::{}", builtin.namespace, builtin.name);
+        if args.len() != builtin.inputs.len() {
+            self.errors.push(CheckError::ArgCount {
+                name: path,
+                expected: builtin.inputs.len(),
+                found: args.len(),
+                span: span.clone(),
+            });
+            return types::unknown();
+        }
+        for (param, (typ, span)) in builtin.inputs.iter().zip(args) {
+            self.check_arg(param, typ, &span);
+        }
+        for (param, arg) in builtin.inputs.iter().zip(exprs) {
+            self.check_inferred(arg, param);
+        }
+        match builtin.outputs.len() {
+            1 => builtin.outputs[0].clone(),
+            _ => ast::Type::tuple(builtin.outputs),
+        }
+    }
+
+    // a method of the receiver's type, where `piped` is the value written
+    // into the call with `->`
+    fn check_method(
+        &mut self,
+        receiver: &ast::Expr,
+        method: &str,
+        args: &[ast::Expr],
+        piped: Option<(ast::Type, ast::Span)>,
+        span: &ast::Span,
+    ) -> ast::Type {
+        let typ = self.check_expr(receiver);
+        let exprs = args;
+        let mut args: Vec<(ast::Type, ast::Span)> = args
+            .iter()
+            .map(|arg| (self.check_expr(arg), arg.span.clone()))
+            .collect();
+        args.extend(piped);
+        if types::is_unknown(&typ) {
+            return types::unknown();
+        }
+        let mut methods: Vec<builtins::Method> = builtins::methods(&typ)
+            .into_iter()
+            .filter(|m| m.name == method)
+            .collect();
+        if methods.is_empty() {
+            // the methods of an option's value are only called once it is
+            // unwrapped
+            let item = types::option_item(&typ);
+            if item.is_some_and(|item| builtins::methods(item).iter().any(|m| m.name == method)) {
+                return self.not_unwrapped(typ, &receiver.span);
+            }
+            self.errors.push(CheckError::MethodNotFound {
+                method: method.to_string(),
+                receiver: typ,
+                span: span.clone(),
+            });
+            return types::unknown();
+        }
+        let found = match methods.iter().position(|m| m.inputs.len() == args.len()) {
+            Some(index) => methods.swap_remove(index),
+            None => {
+                self.errors.push(CheckError::ArgCount {
+                    name: method.to_string(),
+                    expected: methods.iter().map(|m| m.inputs.len()).max().unwrap(),
+                    found: args.len(),
+                    span: span.clone(),
+                });
+                return methods.swap_remove(0).output;
+            }
+        };
+        for (param, (typ, span)) in found.inputs.iter().zip(args) {
+            self.check_arg(param, typ, &span);
+        }
+        for (param, arg) in found.inputs.iter().zip(exprs) {
+            self.check_inferred(arg, param);
+        }
+        found.output
+    }
+
+    // an input of a function written in the program, which may also be given
+    // a stream of bytes to parse as a number
+    fn check_input(&mut self, param: &ast::Type, arg: ast::Type, span: &ast::Span) {
+        if types::parses(param, &arg) {
+            return;
+        }
+        match types::option_item(&arg) {
+            Some(item) if types::parses(param, item) => {
+                self.not_unwrapped(arg, span);
+            }
+            _ => self.check_arg(param, arg, span),
+        }
+    }
+
+    fn check_arg(&mut self, param: &ast::Type, arg: ast::Type, span: &ast::Span) {
+        if types::accepts(param, &arg) {
+            return;
+        }
+        match types::option_item(&arg) {
+            Some(item) if types::accepts(param, item) => self.not_unwrapped(arg, span),
+            _ => self.mismatch(param.clone(), arg, span),
+        };
+    }
+
+    // stream indexes and range bounds are `u32`, as for `get(n)`, though
+    // any integer is accepted
+    fn check_index(&mut self, index: &ast::Expr) {
+        let found = self.check_expr(index);
+        if !types::is_integer(&found) {
+            self.mismatch(ast::Type::new("u32", vec![]), found, &index.span);
+        }
+    }
+
+    // integer literals must fit the type given by their suffix
+    fn check_literal(&mut self, lit: &ast::Lit, negated: bool, span: &ast::Span) {
+        if let ast::Lit::Int {
+            value,
+            suffix: Some(suffix),
+        } = lit
+        {
+            self.check_range(*value, *suffix, negated, span);
+        }
+    }
+
+    // an integer literal without a suffix must fit the type it is used as,
+    // once that type is known
+    fn check_inferred(&mut self, expr: &ast::Expr, typ: &ast::Type) {
+        let (lit, negated) = match &expr.kind {
+            ast::ExprKind::Literal(lit) => (lit, false),
+            ast::ExprKind::PrefixOp { op, expr: inner } if *op == T![-] => match &inner.kind {
+                ast::ExprKind::Literal(lit) => (lit, true),
+                _ => return,
+            },
+            _ => return,
+        };
+        let suffix = ast::IntSuffix::from_name(&types::unwrap_option(typ).name);
+        if let (
+            ast::Lit::Int {
+                value,
+                suffix: None,
+            },
+            Some(suffix),
+        ) = (lit, suffix)
+        {
+            self.check_range(*value, suffix, negated, &expr.span);
+        }
+    }
+
+    fn check_range(&mut self, value: u64, suffix: ast::IntSuffix, negated: bool, span: &ast::Span) {
+        let fits = if negated && !suffix.is_signed() {
+            value == 0
+        } else {
+            value <= suffix.max(negated)
+        };
+        if !fits {
+            self.errors.push(CheckError::LiteralOutOfRange {
+                suffix,
+                span: span.clone(),
+            });
+        }
+    }
+}
+
+// `a::b::c` is the function `c` of the namespace `a::b`
+fn split_path(segments: &[String]) -> (String, &str) {
+    let (name, namespace) = segments.split_last().expect("empty path");
+    (namespace.join("::"), name)
+}
+
+fn is_sink_call(expr: &ast::Expr) -> bool {
+    matches!(&expr.kind, ast::ExprKind::PathCall { path, .. } if builtins::is_sink(path))
+}
+
+fn integer() -> ast::Type {
+    ast::Type::new("{integer}", vec![])
+}
+
+// the integer type an operand was expected to have, for error messages
+fn known_integer(typ: &ast::Type) -> ast::Type {
+    if types::is_unknown(typ) {
+        integer()
+    } else {
+        typ.clone()
+    }
 }
@@ -0,0 +1,93 @@
+use crate::parser::ast::Type;
+use crate::span::Span;
+use std::collections::HashMap;
+
+// every parameter, output and variable declared with `:=` or by a `for`
+// loop gets its own symbol; each identifier that refers to it resolves to
+// the symbol's id
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SymbolId(pub usize);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub name: String,
+    pub typ: Type,
+    // where the symbol is declared
+    pub span: Span,
+}
+
+#[derive(Default)]
+pub struct Scopes {
+    symbols: Vec<Symbol>,
+    // innermost scope last
+    stack: Vec<HashMap<String, SymbolId>>,
+    // identifiers are found by their span
+    resolved: HashMap<Span, SymbolId>,
+}
+
+impl Scopes {
+    pub fn push(&mut self) {
+        self.stack.push(HashMap::new());
+    }
+
+    pub fn pop(&mut self) {
+        self.stack.pop();
+    }
+
+    // declares `name` in the innermost scope, or gives the span of the first
+    // declaration when the name is already declared there
+    pub fn declare(&mut self, name: &str, typ: Type, span: &Span) -> Result<SymbolId, Span> {
+        let scope = self.stack.last_mut().expect("no scope to declare in");
+        if let Some(id) = scope.get(name) {
+            return Err(self.symbols[id.0].span.clone());
+        }
+        let id = SymbolId(self.symbols.len());
+        scope.insert(name.to_string(), id);
+        self.symbols.push(Symbol {
+            name: name.to_string(),
+            typ,
+            span: span.clone(),
+        });
+        Ok(id)
+    }
+
+    pub fn lookup(&self, name: &str) -> Option<SymbolId> {
+        self.stack
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).copied())
+    }
+
+    // resolves the identifier at `span` and remembers what it refers to
+    pub fn resolve(&mut self, name: &str, span: &Span) -> Option<SymbolId> {
+        let id = self.lookup(name)?;
+        self.resolved.insert(span.clone(), id);
+        Some(id)
+    }
+
+    pub fn resolved(&self, span: &Span) -> Option<SymbolId> {
+        self.resolved.get(span).copied()
+    }
+
+    pub fn symbol(&self, id: SymbolId) -> &Symbol {
+        &self.symbols[id.0]
+    }
+}
+
+#[test]
+fn shadowing() {
+    let mut scopes = Scopes::default();
+    let typ = || Type::new("u8", vec![]);
+    scopes.push();
+    let outer = scopes.declare("x", typ(), &(0..1)).unwrap();
+    assert_eq!(scopes.declare("x", typ(), &(2..3)), Err(0..1));
+    scopes.push();
+    let inner = scopes.declare("x", typ(), &(4..5)).unwrap();
+    assert_ne!(outer, inner);
+    assert_eq!(scopes.resolve("x", &(6..7)), Some(inner));
+    scopes.pop();
+    assert_eq!(scopes.resolve("x", &(8..9)), Some(outer));
+    assert_eq!(scopes.resolve("y", &(10..11)), None);
+    assert_eq!(scopes.resolved(&(6..7)), Some(inner));
+    assert_eq!(scopes.symbol(outer).span, 0..1);
+}
@@ -0,0 +1,131 @@
+use crate::parser::ast::{Lit, Type};
+
+// `_` stands for a type that is not known, e.g. of an undefined name or of a
+// call into a namespace; it fits anywhere so that one mistake is reported
+// once
+pub fn unknown() -> Type {
+    Type::new("_", vec![])
+}
+
+pub fn is_unknown(typ: &Type) -> bool {
+    typ.name == "_"
+}
+
+pub fn bool() -> Type {
+    Type::new("bool", vec![])
+}
+
+pub fn stream(item: Type) -> Type {
+    Type::new("stream", vec![item])
+}
+
+pub fn option(item: Type) -> Type {
+    Type::new("option", vec![item])
+}
+
+// integer literals without a suffix get their type from how they are used,
+// which is shown as `{integer}` until then
+pub fn literal_type(lit: &Lit) -> Type {
+    match lit {
+        Lit::Int {
+            suffix: Some(suffix),
+            ..
+        } => Type::new(suffix.name(), vec![]),
+        Lit::Int { suffix: None, .. } => Type::new("{integer}", vec![]),
+        Lit::Str(_) => stream(Type::new("u8", vec![])),
+        Lit::Byt(_) => Type::new("u8", vec![]),
+        Lit::Bool(_) => bool(),
+        // the item type of `none` comes from how it is used
+        Lit::None => option(unknown()),
+    }
+}
+
+pub fn is_integer(typ: &Type) -> bool {
+    matches!(
+        typ.name.as_str(),
+        "_" | "{integer}" | "u8" | "u16" | "u32" | "u64" | "i8" | "i16" | "i32" | "i64"
+    )
+}
+
+// the most precise type that both `a` and `b` fit, if there is one
+pub fn unify(a: &Type, b: &Type) -> Option<Type> {
+    match (a.name.as_str(), b.name.as_str()) {
+        ("_", _) => Some(b.clone()),
+        (_, "_") => Some(a.clone()),
+        ("{integer}", _) if is_integer(b) => Some(b.clone()),
+        (_, "{integer}") if is_integer(a) => Some(a.clone()),
+        (x, y) if x == y && a.generics.len() == b.generics.len() => {
+            let generics = a
+                .generics
+                .iter()
+                .zip(b.generics.iter())
+                .map(|(a, b)| unify(a, b))
+                .collect::<Option<Vec<Type>>>()?;
+            Some(Type::new(x, generics))
+        }
+        _ => None,
+    }
+}
+
+// a value may be given where an option holding it is expected, whether it
+// is assigned, returned, written or passed as an argument; integers of one
+// type are not converted to another
+pub fn accepts(to: &Type, value: &Type) -> bool {
+    unify(to, value).is_some()
+        || to.name == "option" && to.generics.len() == 1 && unify(&to.generics[0], value).is_some()
+}
+
+// a stream of bytes given to an integer input of a function is parsed as a
+// number when the function is called
+pub fn parses(param: &Type, arg: &Type) -> bool {
+    is_integer(param) && arg == &stream(Type::new("u8", vec![]))
+}
+
+// the value held by an option, which `?` unwraps
+pub fn option_item(typ: &Type) -> Option<&Type> {
+    match typ.name.as_str() {
+        "option" if typ.generics.len() == 1 => Some(&typ.generics[0]),
+        _ => None,
+    }
+}
+
+// an option holding a value is the value itself when the program runs, so
+// it may be written to a stream of its values, where `none` writes nothing
+pub fn unwrap_option(typ: &Type) -> &Type {
+    option_item(typ).unwrap_or(typ)
+}
+
+// the item type of a stream
+pub fn item_type(typ: &Type) -> Option<Type> {
+    match typ.name.as_str() {
+        "_" => Some(unknown()),
+        "stream" if typ.generics.len() == 1 => Some(typ.generics[0].clone()),
+        _ => None,
+    }
+}
+
+#[test]
+fn unify_types() {
+    let u8 = Type::new("u8", vec![]);
+    let int = Type::new("{integer}", vec![]);
+    assert_eq!(unify(&int, &u8), Some(u8.clone()));
+    assert_eq!(unify(&u8, &bool()), None);
+    assert_eq!(
+        unify(&option(unknown()), &option(stream(u8.clone()))),
+        Some(option(stream(u8.clone())))
+    );
+    assert_eq!(unify(&stream(u8.clone()), &Type::new("u32", vec![])), None);
+    assert!(accepts(&option(u8.clone()), &u8));
+    assert!(accepts(&option(unknown()), &int));
+    assert!(!accepts(&u8, &option(u8.clone())));
+    assert!(!accepts(&u8, &Type::new("u32", vec![])));
+    let u32 = Type::new("u32", vec![]);
+    assert!(parses(&u32, &stream(u8.clone())));
+    assert!(!parses(&u32, &option(stream(u8.clone()))));
+    assert!(!parses(&u32, &stream(u32.clone())));
+    assert!(!accepts(&u32, &stream(u8.clone())));
+    assert_eq!(
+        Type::tuple(vec![u8, stream(int)]).to_string(),
+        "(u8, stream<{integer}>)"
+    );
+}
@@ -0,0 +1,134 @@
+use crate::checker::builtins;
+use crate::parser::ast::{Expr, ExprKind, Item, ItemKind, Stmt, StmtKind};
+
+// Lowers syntactic sugar that the parser keeps so that source can be shown
+// as it was written. It runs after checking and before the interpreter:
+// * `value -> f(..)` becomes a call of `f` with the value as an argument;
+//   each item of a tuple fills the first `_`, or is added to the end of
+//   the arguments when there is none left
+// * `value -> s.m(..)` and `value -> NS::f(..)` add the value to the end of
+//   the arguments
+// * `value -> SINKS::f(..);` becomes a write to the stream the sink gives
+
+pub fn desugar(items: &mut [Item]) {
+    for item in items {
+        match &mut item.kind {
+            ItemKind::Function { body, .. } => block(body),
+            ItemKind::Error => {}
+        }
+    }
+}
+
+fn block(stmts: &mut [Stmt]) {
+    for stmt in stmts {
+        statement(stmt);
+    }
+}
+
+fn statement(stmt: &mut Stmt) {
+    if let StmtKind::Expr(Expr {
+        kind: ExprKind::Pipe { value, call },
+        ..
+    }) = &mut stmt.kind
+    {
+        if matches!(&call.kind, ExprKind::PathCall { path, .. } if builtins::is_sink(path)) {
+            let value = std::mem::replace(&mut **value, ExprKind::Placeholder.into());
+            let stream = std::mem::replace(&mut **call, ExprKind::Placeholder.into());
+            stmt.kind = StmtKind::StreamWrite { value, stream };
+        }
+    }
+    match &mut stmt.kind {
+        StmtKind::Declaration { value, .. }
+        | StmtKind::Assignment { value, .. }
+        | StmtKind::CompoundAssignment { value, .. }
+        | StmtKind::Expr(value) => expression(value),
+        StmtKind::StreamWrite { value, stream } => {
+            expression(value);
+            expression(stream);
+        }
+        StmtKind::IfStmt {
+            condition,
+            body,
+            else_stmt,
+        } => {
+            expression(condition);
+            block(body);
+            if let Some(else_stmt) = else_stmt {
+                statement(else_stmt);
+            }
+        }
+        StmtKind::ReturnStmt { values } => values.iter_mut().for_each(expression),
+        StmtKind::ForLoop { stream, stmts, .. } => {
+            expression(stream);
+            block(stmts);
+        }
+        StmtKind::WhileLoop { condition, stmts } => {
+            expression(condition);
+            block(stmts);
+        }
+        StmtKind::Block { stmts } => block(stmts),
+        StmtKind::Assert { condition, message } => {
+            expression(condition);
+            message.iter_mut().for_each(expression);
+        }
+        StmtKind::Increment { .. }
+        | StmtKind::Decrement { .. }
+        | StmtKind::ContinueStmt
+        | StmtKind::BreakStmt
+        | StmtKind::Error => {}
+    }
+}
+
+fn expression(expr: &mut Expr) {
+    match &mut expr.kind {
+        ExprKind::Pipe { value, call } => {
+            expression(value);
+            expression(call);
+            let value = std::mem::replace(&mut **value, ExprKind::Placeholder.into());
+            let mut call = std::mem::replace(&mut call.kind, ExprKind::Placeholder);
+            match &mut call {
+                ExprKind::FnCall { args, .. } => {
+                    let values = match value.kind {
+                        ExprKind::Tuple(items) => items,
+                        _ => vec![value],
+                    };
+                    for value in values {
+                        match args.iter().position(|a| a.kind == ExprKind::Placeholder) {
+                            Some(index) => args[index] = value,
+                            None => args.push(value),
+                        }
+                    }
+                }
+                ExprKind::PathCall { args, .. } | ExprKind::MethodCall { args, .. } => {
+                    args.push(value)
+                }
+                _ => unreachable!("the parser only pipes into calls"),
+            }
+            expr.kind = call;
+        }
+        ExprKind::PrefixOp { expr, .. } | ExprKind::PostfixOp { expr, .. } => expression(expr),
+        ExprKind::InfixOp { lhs, rhs, .. } => {
+            expression(lhs);
+            expression(rhs);
+        }
+        ExprKind::Index { expr, index } => {
+            expression(expr);
+            expression(index);
+        }
+        ExprKind::Range { start, end } => {
+            start.iter_mut().for_each(|e| expression(e));
+            end.iter_mut().for_each(|e| expression(e));
+        }
+        ExprKind::FnCall { args, .. } | ExprKind::PathCall { args, .. } | ExprKind::Tuple(args) => {
+            args.iter_mut().for_each(expression)
+        }
+        ExprKind::MethodCall { receiver, args, .. } => {
+            expression(receiver);
+            args.iter_mut().for_each(expression);
+        }
+        ExprKind::Literal(_)
+        | ExprKind::Ident(_)
+        | ExprKind::Path { .. }
+        | ExprKind::Placeholder => {}
+    }
+}
@@ -0,0 +1,231 @@
+use crate::checker::CheckError;
+use crate::interpreter::RuntimeError;
+use crate::parser::ParseError;
+use crate::span::{line_col, Span};
+use crate::T;
+use std::fmt::Write;
+
+// Error codes
+// - E00xx: lexing and parsing
+// - E01xx: declarations and names
+// - E02xx: types
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub code: Option<&'static str>,
+    pub message: String,
+    // primary location, underlined with `^`; `None` for errors that are
+    // about the whole file
+    pub span: Option<Span>,
+    // secondary locations, underlined with `-` and explained by their text
+    pub labels: Vec<(Span, String)>,
+    // extra lines of explanation, shown after the source
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn error(code: &'static str, message: String, span: Option<Span>) -> Self {
+        Self {
+            code: Some(code),
+            message,
+            span,
+            labels: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn with_label(mut self, span: Span, message: &str) -> Self {
+        self.labels.push((span, message.to_string()));
+        self
+    }
+
+    pub fn with_note(mut self, note: String) -> Self {
+        self.notes.push(note);
+        self
+    }
+
+    // render in the usual compiler style, with notes at the end:
+    //
+    // error[E0100]: redeclaration of function name `main`
+    //  --> example.burn:3:1
+    //   |
+    // 1 | fn main() {}
+    //   | ------------ first declared here
+    // 2 |
+    // 3 | fn main() {}
+    //   | ^^^^^^^^^^^^
+    pub fn render(&self, filename: &str, source: &str) -> String {
+        let mut out = String::new();
+        match self.code {
+            Some(code) => write!(out, "error[{}]: {}", code, self.message).unwrap(),
+            None => write!(out, "error: {}", self.message).unwrap(),
+        }
+        let span = match &self.span {
+            Some(span) => span,
+            None => {
+                writeln!(out, "\n --> {}", filename).unwrap();
+                for note in &self.notes {
+                    writeln!(out, "  = {}", note).unwrap();
+                }
+                return out;
+            }
+        };
+        let mut marks = vec![Mark::new(source, span, '^', "")];
+        for (span, text) in &self.labels {
+            marks.push(Mark::new(source, span, '-', text));
+        }
+        marks.sort_by_key(|m| (m.line, m.start));
+
+        let lines: Vec<&str> = source.split('\n').collect();
+        let source_line = |n: usize| lines.get(n - 1).unwrap_or(&"").trim_end_matches('\r');
+        let width = marks
+            .iter()
+            .map(|m| m.line)
+            .max()
+            .unwrap()
+            .to_string()
+            .len();
+        let gutter = " ".repeat(width);
+        let (line, col) = line_col(source, span.start);
+        writeln!(out, "\n{}--> {}:{}:{}", gutter, filename, line, col).unwrap();
+        writeln!(out, "{} |", gutter).unwrap();
+        let mut prev_line: Option<usize> = None;
+        for mark in &marks {
+            if prev_line != Some(mark.line) {
+                // show a single skipped line, elide longer gaps
+                match prev_line {
+                    Some(prev) if mark.line - prev == 2 => writeln!(
+                        out,
+                        "{:>w$} | {}",
+                        prev + 1,
+                        source_line(prev + 1),
+                        w = width
+                    )
+                    .unwrap(),
+                    Some(prev) if mark.line - prev > 2 => writeln!(out, "...").unwrap(),
+                    _ => {}
+                }
+                writeln!(
+                    out,
+                    "{:>w$} | {}",
+                    mark.line,
+                    source_line(mark.line),
+                    w = width
+                )
+                .unwrap();
+            }
+            // keep tabs so the underline lines up with the source
+            let indent: String = source_line(mark.line)
+                .chars()
+                .take(mark.start - 1)
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            let underline = mark.underline.to_string().repeat(mark.end - mark.start);
+            let annotation = format!("{} | {}{} {}", gutter, indent, underline, mark.text);
+            writeln!(out, "{}", annotation.trim_end()).unwrap();
+            prev_line = Some(mark.line);
+        }
+        for note in &self.notes {
+            writeln!(out, "{} = {}", gutter, note).unwrap();
+        }
+        out
+    }
+}
+
+// an underlined part of a single source line, in 1-based columns
+struct Mark<'a> {
+    line: usize,
+    start: usize,
+    end: usize,
+    underline: char,
+    text: &'a str,
+}
+
+impl<'a> Mark<'a> {
+    // spans covering several lines are only underlined on their first line
+    fn new(source: &str, span: &Span, underline: char, text: &'a str) -> Self {
+        let (line, start) = line_col(source, span.start);
+        let line_end = source[span.start.min(source.len())..]
+            .find('\n')
+            .map(|i| span.start + i)
+            .unwrap_or(source.len());
+        let (_, end) = line_col(source, span.end.min(line_end));
+        Self {
+            line,
+            start,
+            // always underline at least one column, even for empty spans
+            end: end.max(start + 1),
+            underline,
+            text,
+        }
+    }
+}
+
+impl From<&ParseError> for Diagnostic {
+    fn from(err: &ParseError) -> Self {
+        let code = if err.found == T![error] {
+            "E0001"
+        } else {
+            "E0002"
+        };
+        Diagnostic::error(code, err.to_string(), Some(err.span.clone()))
+    }
+}
+
+impl From<&CheckError> for Diagnostic {
+    fn from(err: &CheckError) -> Self {
+        let code = match err {
+            CheckError::FnRedeclared { .. } => "E0100",
+            CheckError::ParamRedeclared { .. } => "E0101",
+            CheckError::MainNotFound => "E0102",
+            CheckError::UndefinedName { .. } => "E0103",
+            CheckError::VarRedeclared { .. } => "E0104",
+            CheckError::FnNotFound { .. } => "E0105",
+            CheckError::BuiltinNotFound { .. } => "E0106",
+            CheckError::MethodNotFound { .. } => "E0107",
+            CheckError::OutsideLoop { .. } => "E0108",
+            CheckError::WriteNotStatement { .. } => "E0109",
+            CheckError::UnknownType { .. } => "E0200",
+            CheckError::WrongGenericCount { .. } => "E0201",
+            CheckError::LiteralOutOfRange { .. } => "E0202",
+            CheckError::TypeMismatch { .. } => "E0203",
+            CheckError::ReturnCount { .. } => "E0204",
+            CheckError::FanOutCount { .. } => "E0205",
+            CheckError::ArgCount { .. } => "E0206",
+            CheckError::UnfilledPlaceholder { .. } => "E0207",
+            CheckError::DestructureCount { .. } => "E0208",
+            CheckError::TupleAssigned { .. } => "E0209",
+            CheckError::PostfixUnsupported { .. } => "E0210",
+            CheckError::OptionNotUnwrapped { .. } => "E0211",
+        };
+        let diagnostic = Diagnostic::error(code, err.to_string(), err.span());
+        match err {
+            CheckError::FnRedeclared { first, .. }
+            | CheckError::ParamRedeclared { first, .. }
+            | CheckError::VarRedeclared { first, .. } => {
+                diagnostic.with_label(first.clone(), "first declared here")
+            }
+            CheckError::BuiltinNotFound {
+                suggestion: Some(suggestion),
+                ..
+            } => diagnostic.with_note(format!("help: did you mean `{}`?", suggestion)),
+            _ => diagnostic,
+        }
+    }
+}
+
+// runtime errors have no code, as they are not found by the compiler; any
+// lines after the first one of the message are shown as notes
+impl From<&RuntimeError> for Diagnostic {
+    fn from(err: &RuntimeError) -> Self {
+        let text = err.to_string();
+        let mut lines = text.lines();
+        Diagnostic {
+            code: None,
+            message: lines.next().unwrap_or_default().to_string(),
+            span: Some(err.span()),
+            labels: Vec::new(),
+            notes: lines.map(|line| line.trim().to_string()).collect(),
+        }
+    }
+}
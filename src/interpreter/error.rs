@@ -0,0 +1,86 @@
+use crate::span::Span;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuntimeError {
+    // `assert` whose condition is false
+    AssertFailed {
+        condition: String,
+        message: Option<String>,
+        span: Span,
+    },
+    // `assert left == right` where the two sides differ; `index` is the
+    // first position where two streams differ
+    AssertNotEqual {
+        left: String,
+        right: String,
+        index: Option<usize>,
+        message: Option<String>,
+        span: Span,
+    },
+    // anything else that stops the program, such as reading past the end
+    // of a stream or dividing by zero
+    Invalid {
+        message: String,
+        span: Span,
+    },
+    // `?` applied to `none`; the function it is in returns at once, so this
+    // never reaches the caller of `Interpreter::call`
+    NonePropagated {
+        span: Span,
+    },
+}
+
+impl RuntimeError {
+    pub fn invalid(message: String, span: &Span) -> Self {
+        RuntimeError::Invalid {
+            message,
+            span: span.clone(),
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            RuntimeError::AssertFailed { span, .. }
+            | RuntimeError::AssertNotEqual { span, .. }
+            | RuntimeError::Invalid { span, .. }
+            | RuntimeError::NonePropagated { span } => span.clone(),
+        }
+    }
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            RuntimeError::AssertFailed {
+                condition, message, ..
+            } => {
+                write!(f, "assertion failed: `{}`", condition)?;
+                if let Some(message) = message {
+                    write!(f, ": {}", message)?;
+                }
+                Ok(())
+            }
+            RuntimeError::AssertNotEqual {
+                left,
+                right,
+                index,
+                message,
+                ..
+            } => {
+                write!(f, "assertion `left == right` failed")?;
+                if let Some(message) = message {
+                    write!(f, ": {}", message)?;
+                }
+                if let Some(index) = index {
+                    write!(f, "\n index: {}", index)?;
+                }
+                write!(f, "\n  left: {}\n right: {}", left, right)
+            }
+            RuntimeError::Invalid { message, .. } => write!(f, "{}", message),
+            RuntimeError::NonePropagated { .. } => write!(f, "`?` found `none`"),
+        }
+    }
+}
+
+impl std::error::Error for RuntimeError {}
@@ -0,0 +1,845 @@
+mod error;
+mod value;
+
+pub use error::RuntimeError;
+pub use value::{Stream, Value};
+
+use crate::lexer::Token;
+use crate::parser::ast;
+use crate::span::Span;
+use crate::T;
+use std::collections::HashMap;
+use value::first_difference;
+
+// Runs a program by walking its AST, once `desugar` has run over it. Streams
+// are evaluated eagerly: a function writing to a stream runs to completion
+// before anything reads from it.
+
+pub struct Interpreter<'a> {
+    fns: HashMap<&'a str, Function<'a>>,
+    frame: Frame<'a>,
+}
+
+#[derive(Clone, Copy)]
+struct Function<'a> {
+    parameters: &'a [ast::Param],
+    body: &'a [ast::Stmt],
+    return_params: &'a [ast::Param],
+}
+
+// variables of the function being run, innermost scope last; the outermost
+// scope holds the parameters and outputs
+#[derive(Default)]
+struct Frame<'a> {
+    scopes: Vec<HashMap<String, Value>>,
+    outputs: &'a [ast::Param],
+}
+
+// how control leaves a statement
+#[derive(Debug, PartialEq)]
+enum Flow {
+    Next,
+    Break,
+    Continue,
+    Return,
+}
+
+impl<'a> Interpreter<'a> {
+    pub fn new(items: &'a [ast::Item]) -> Self {
+        let mut fns = HashMap::new();
+        for item in items {
+            if let ast::ItemKind::Function {
+                name,
+                parameters,
+                body,
+                return_params,
+            } = &item.kind
+            {
+                fns.insert(
+                    name.as_str(),
+                    Function {
+                        parameters,
+                        body,
+                        return_params,
+                    },
+                );
+            }
+        }
+        Self {
+            fns,
+            frame: Frame::default(),
+        }
+    }
+
+    // call a function with the given inputs and return its outputs
+    pub fn call(&mut self, name: &str, args: Vec<Value>) -> Result<Vec<Value>, RuntimeError> {
+        self.call_fn(name, args, &(0..0))
+    }
+
+    fn call_fn(
+        &mut self,
+        name: &str,
+        args: Vec<Value>,
+        span: &Span,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        let function = match self.fns.get(name) {
+            Some(function) => *function,
+            None => {
+                return Err(RuntimeError::invalid(
+                    format!("cannot find function `{}`", name),
+                    span,
+                ))
+            }
+        };
+        if args.len() != function.parameters.len() {
+            return Err(RuntimeError::invalid(
+                format!(
+                    "`{}` takes {} argument{}, found {}",
+                    name,
+                    function.parameters.len(),
+                    if function.parameters.len() == 1 {
+                        ""
+                    } else {
+                        "s"
+                    },
+                    args.len()
+                ),
+                span,
+            ));
+        }
+        let mut scope = HashMap::new();
+        for (param, arg) in function.parameters.iter().zip(args) {
+            // a stream of bytes given to an integer input is parsed as a number
+            let arg = match arg {
+                Value::Stream(_) | Value::None if is_integer(&param.typ) => {
+                    Value::Int(parse_number(&arg, span)?)
+                }
+                arg => arg,
+            };
+            scope.insert(param.name.clone(), coerce(arg, &param.typ));
+        }
+        for param in function.return_params {
+            scope.insert(param.name.clone(), default_value(&param.typ));
+        }
+        let frame = Frame {
+            scopes: vec![scope],
+            outputs: function.return_params,
+        };
+        let caller = std::mem::replace(&mut self.frame, frame);
+        let result = self.exec_stmts(function.body);
+        let mut frame = std::mem::replace(&mut self.frame, caller);
+        // `none` propagated by `?` makes every `option` output `none`; the
+        // other outputs keep what was assigned to them
+        let propagated = matches!(result, Err(RuntimeError::NonePropagated { .. }));
+        if !propagated {
+            result?;
+        }
+        let mut scope = frame.scopes.swap_remove(0);
+        Ok(function
+            .return_params
+            .iter()
+            .map(|output| match scope.remove(&output.name).unwrap() {
+                _ if propagated && output.typ.name == "option" => Value::None,
+                value => value,
+            })
+            .collect())
+    }
+
+    fn exec_stmts(&mut self, stmts: &[ast::Stmt]) -> Result<Flow, RuntimeError> {
+        for stmt in stmts {
+            let flow = self.exec(stmt)?;
+            if flow != Flow::Next {
+                return Ok(flow);
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    fn exec_block(&mut self, stmts: &[ast::Stmt]) -> Result<Flow, RuntimeError> {
+        self.frame.scopes.push(HashMap::new());
+        let flow = self.exec_stmts(stmts);
+        self.frame.scopes.pop();
+        flow
+    }
+
+    fn exec(&mut self, stmt: &ast::Stmt) -> Result<Flow, RuntimeError> {
+        let span = &stmt.span;
+        match &stmt.kind {
+            ast::StmtKind::Declaration { var_names, value } => {
+                let values = self.eval_targets(value, var_names.len(), span)?;
+                for (name, value) in var_names.iter().zip(values) {
+                    if name.name != "_" {
+                        self.declare(&name.name, value);
+                    }
+                }
+            }
+            ast::StmtKind::Assignment { var_names, value } => {
+                let values = self.eval_targets(value, var_names.len(), span)?;
+                // `_` discards the value it is given
+                for (name, value) in var_names.iter().zip(values) {
+                    if name.name != "_" {
+                        self.assign(&name.name, value, &name.span)?;
+                    }
+                }
+            }
+            ast::StmtKind::CompoundAssignment {
+                var_name,
+                op,
+                value,
+            } => {
+                let current = self.lookup(&var_name.name, &var_name.span)?;
+                let value = self.eval(value)?;
+                let value = binary(*op, current, value, span)?;
+                self.assign(&var_name.name, value, &var_name.span)?;
+            }
+            ast::StmtKind::Increment { var_name } | ast::StmtKind::Decrement { var_name } => {
+                let op = match stmt.kind {
+                    ast::StmtKind::Increment { .. } => T![+],
+                    _ => T![-],
+                };
+                let current = self.lookup(&var_name.name, &var_name.span)?;
+                let value = binary(op, current, Value::Int(1), span)?;
+                self.assign(&var_name.name, value, &var_name.span)?;
+            }
+            ast::StmtKind::IfStmt {
+                condition,
+                body,
+                else_stmt,
+            } => {
+                if self.condition(condition)? {
+                    return self.exec_block(body);
+                }
+                if let Some(else_stmt) = else_stmt {
+                    return self.exec(else_stmt);
+                }
+            }
+            ast::StmtKind::ReturnStmt { values } => {
+                let outputs = self.frame.outputs;
+                if values.len() > outputs.len() {
+                    return Err(RuntimeError::invalid(
+                        format!(
+                            "expected at most {} return values, found {}",
+                            outputs.len(),
+                            values.len()
+                        ),
+                        span,
+                    ));
+                }
+                // `_` leaves the output as it is
+                let mut returned = Vec::new();
+                for (value, output) in values.iter().zip(outputs) {
+                    if value.kind != ast::ExprKind::Placeholder {
+                        returned.push((&output.name, coerce(self.eval(value)?, &output.typ)));
+                    }
+                }
+                for (name, value) in returned {
+                    self.frame.scopes[0].insert(name.clone(), value);
+                }
+                return Ok(Flow::Return);
+            }
+            ast::StmtKind::ContinueStmt => return Ok(Flow::Continue),
+            ast::StmtKind::BreakStmt => return Ok(Flow::Break),
+            ast::StmtKind::ForLoop {
+                var_name,
+                stream,
+                stmts,
+            } => {
+                let stream = self.eval_stream(stream)?;
+                loop {
+                    let item = stream.borrow_mut().pop_front();
+                    let item = match item {
+                        Some(item) => item,
+                        None => break,
+                    };
+                    let mut scope = HashMap::new();
+                    scope.insert(var_name.name.clone(), item);
+                    self.frame.scopes.push(scope);
+                    let flow = self.exec_block(stmts);
+                    self.frame.scopes.pop();
+                    match flow? {
+                        Flow::Break => break,
+                        Flow::Return => return Ok(Flow::Return),
+                        Flow::Next | Flow::Continue => {}
+                    }
+                }
+            }
+            ast::StmtKind::WhileLoop { condition, stmts } => {
+                while self.condition(condition)? {
+                    match self.exec_block(stmts)? {
+                        Flow::Break => break,
+                        Flow::Return => return Ok(Flow::Return),
+                        Flow::Next | Flow::Continue => {}
+                    }
+                }
+            }
+            ast::StmtKind::Block { stmts } => return self.exec_block(stmts),
+            ast::StmtKind::Assert { condition, message } => {
+                self.assert(condition, message.as_ref(), span)?
+            }
+            ast::StmtKind::Expr(expr) => {
+                self.eval_multi(expr)?;
+            }
+            ast::StmtKind::StreamWrite { value, stream } => self.write(value, stream)?,
+            ast::StmtKind::Error => {
+                return Err(RuntimeError::invalid(
+                    "cannot run a statement that failed to parse".to_string(),
+                    span,
+                ))
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    // `assert left == right` compares both sides as values, so streams are
+    // compared item by item and the report shows where they differ
+    fn assert(
+        &mut self,
+        condition: &ast::Expr,
+        message: Option<&ast::Expr>,
+        span: &Span,
+    ) -> Result<(), RuntimeError> {
+        let mut failure = match &condition.kind {
+            ast::ExprKind::InfixOp {
+                op: T![==],
+                lhs,
+                rhs,
+            } => {
+                let left = self.eval(lhs)?;
+                let right = self.eval(rhs)?;
+                if left == right {
+                    return Ok(());
+                }
+                RuntimeError::AssertNotEqual {
+                    index: first_difference(&left, &right),
+                    left: left.to_string(),
+                    right: right.to_string(),
+                    message: None,
+                    span: span.clone(),
+                }
+            }
+            _ => {
+                if self.condition(condition)? {
+                    return Ok(());
+                }
+                RuntimeError::AssertFailed {
+                    condition: condition.to_string(),
+                    message: None,
+                    span: span.clone(),
+                }
+            }
+        };
+        // the message is only evaluated once the assertion has failed
+        if let Some(message) = message {
+            let value = self.eval(message)?;
+            let text = match value.to_bytes() {
+                Some(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+                None => value.to_string(),
+            };
+            match &mut failure {
+                RuntimeError::AssertFailed { message, .. }
+                | RuntimeError::AssertNotEqual { message, .. } => *message = Some(text),
+                RuntimeError::Invalid { .. } | RuntimeError::NonePropagated { .. } => {}
+            }
+        }
+        Err(failure)
+    }
+
+    // evaluate an expression that may produce any number of values, such as
+    // a call to a function with several outputs
+    fn eval_multi(&mut self, expr: &ast::Expr) -> Result<Vec<Value>, RuntimeError> {
+        match &expr.kind {
+            ast::ExprKind::FnCall { fn_name, args } => {
+                let args = self.eval_args(args)?;
+                self.call_fn(fn_name, args, &expr.span)
+            }
+            ast::ExprKind::Tuple(items) => self.eval_args(items),
+            ast::ExprKind::PathCall { path, args } => {
+                let args = self.eval_args(args)?;
+                self.builtin(&path.to_string(), args, &expr.span)
+            }
+            ast::ExprKind::MethodCall {
+                receiver,
+                method: name,
+                args,
+            } => {
+                let receiver = self.eval(receiver)?;
+                let args = self.eval_args(args)?;
+                Ok(vec![method(receiver, name, &args, &expr.span)?])
+            }
+            _ => Ok(vec![self.eval(expr)?]),
+        }
+    }
+
+    fn eval(&mut self, expr: &ast::Expr) -> Result<Value, RuntimeError> {
+        let span = &expr.span;
+        let value = match &expr.kind {
+            ast::ExprKind::Literal(lit) => literal(lit),
+            ast::ExprKind::Ident(name) => self.lookup(name, span)?,
+            ast::ExprKind::Placeholder => {
+                return Err(RuntimeError::invalid(
+                    "`_` was not replaced by a value".to_string(),
+                    span,
+                ))
+            }
+            ast::ExprKind::PrefixOp { op, expr } => {
+                let value = self.eval(expr)?;
+                prefix(*op, value, span)?
+            }
+            ast::ExprKind::PostfixOp { op: T![?], expr } => match self.eval(expr)? {
+                Value::None => return Err(RuntimeError::NonePropagated { span: span.clone() }),
+                value => value,
+            },
+            // `?` is the only postfix operator with a meaning
+            ast::ExprKind::PostfixOp { op, .. } => {
+                return Err(RuntimeError::invalid(
+                    format!("postfix `{}` is not supported", op),
+                    span,
+                ))
+            }
+            ast::ExprKind::Index { expr, index } => {
+                let stream = self.eval_stream(expr)?;
+                self.index(&stream, index)?
+            }
+            ast::ExprKind::Range { .. } => {
+                return Err(RuntimeError::invalid(
+                    "ranges can only be used to index a stream".to_string(),
+                    span,
+                ))
+            }
+            ast::ExprKind::InfixOp {
+                op: T![&&],
+                lhs,
+                rhs,
+            } => Value::Bool(self.condition(lhs)? && self.condition(rhs)?),
+            ast::ExprKind::InfixOp {
+                op: T![||],
+                lhs,
+                rhs,
+            } => Value::Bool(self.condition(lhs)? || self.condition(rhs)?),
+            ast::ExprKind::Path { .. } => {
+                return Err(RuntimeError::invalid(
+                    format!("expected a value, found `{}`", expr),
+                    span,
+                ))
+            }
+            ast::ExprKind::InfixOp { op, lhs, rhs } => {
+                let lhs = self.eval(lhs)?;
+                let rhs = self.eval(rhs)?;
+                binary(*op, lhs, rhs, span)?
+            }
+            ast::ExprKind::Pipe { .. } => {
+                return Err(RuntimeError::invalid(
+                    "`->` into a call must be desugared before running".to_string(),
+                    span,
+                ))
+            }
+            _ => {
+                let mut values = self.eval_multi(expr)?;
+                if values.len() != 1 {
+                    return Err(RuntimeError::invalid(
+                        format!("expected a single value, found {}", values.len()),
+                        span,
+                    ));
+                }
+                values.pop().unwrap()
+            }
+        };
+        Ok(value)
+    }
+
+    // `s[i]` is the same as `s.get(i)`, and `s[a..b]` is a stream of the
+    // items from `a` up to `b`, copied as streams are eager here; neither
+    // consumes anything from `s`
+    fn index(&mut self, stream: &Stream, index: &ast::Expr) -> Result<Value, RuntimeError> {
+        let (start, end) = match &index.kind {
+            ast::ExprKind::Range { start, end } => (start, end),
+            _ => {
+                let n = self.eval(index)?;
+                return method(Value::Stream(stream.clone()), "get", &[n], &index.span);
+            }
+        };
+        let len = stream.borrow().len();
+        let mut bound = |bound: &Option<Box<ast::Expr>>, default: usize| match bound {
+            Some(expr) => match self.eval(expr)?.as_int() {
+                Some(n) if n >= 0 => Ok(n as usize),
+                _ => Err(RuntimeError::invalid(
+                    format!("expected a stream index, found `{}`", expr),
+                    &expr.span,
+                )),
+            },
+            None => Ok(default),
+        };
+        let start = bound(start, 0)?;
+        let end = bound(end, len)?;
+        if start > end || end > len {
+            return Err(RuntimeError::invalid(
+                format!(
+                    "range `{}..{}` is out of bounds for a stream of {} item{}",
+                    start,
+                    end,
+                    len,
+                    if len == 1 { "" } else { "s" }
+                ),
+                &index.span,
+            ));
+        }
+        let items: Vec<Value> = stream.borrow().range(start..end).cloned().collect();
+        Ok(Value::stream(items))
+    }
+
+    fn eval_args(&mut self, args: &[ast::Expr]) -> Result<Vec<Value>, RuntimeError> {
+        args.iter().map(|arg| self.eval(arg)).collect()
+    }
+
+    // targets of a declaration or assignment take the values in order
+    fn eval_targets(
+        &mut self,
+        value: &ast::Expr,
+        count: usize,
+        span: &Span,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        let values = self.eval_multi(value)?;
+        if values.len() != count {
+            return Err(RuntimeError::invalid(
+                format!("expected {} values, found {}", count, values.len()),
+                span,
+            ));
+        }
+        Ok(values)
+    }
+
+    fn eval_stream(&mut self, expr: &ast::Expr) -> Result<Stream, RuntimeError> {
+        match self.eval(expr)? {
+            Value::Stream(stream) => Ok(stream),
+            value => Err(RuntimeError::invalid(
+                format!("expected a stream, found {}", value),
+                &expr.span,
+            )),
+        }
+    }
+
+    fn condition(&mut self, expr: &ast::Expr) -> Result<bool, RuntimeError> {
+        match self.eval(expr)? {
+            Value::Bool(b) => Ok(b),
+            value => Err(RuntimeError::invalid(
+                format!("expected `bool`, found {}", value),
+                &expr.span,
+            )),
+        }
+    }
+
+    // `value -> stream;` appends to the stream; a stream written to another
+    // one is drained into it, and writing `none` does nothing
+    // - `values -> (a, b);` writes each value to the stream in its place
+    fn write(&mut self, value: &ast::Expr, target: &ast::Expr) -> Result<(), RuntimeError> {
+        let targets = match &target.kind {
+            ast::ExprKind::Tuple(targets) => targets,
+            _ => {
+                let value = self.eval(value)?;
+                let target = self.eval_stream(target)?;
+                write_value(value, &target);
+                return Ok(());
+            }
+        };
+        let values = self.eval_multi(value)?;
+        if values.len() != targets.len() {
+            return Err(RuntimeError::invalid(
+                format!(
+                    "expected {} values to write to {} streams, found {}",
+                    targets.len(),
+                    targets.len(),
+                    values.len()
+                ),
+                &value.span,
+            ));
+        }
+        for (value, target) in values.into_iter().zip(targets) {
+            let target = self.eval_stream(target)?;
+            write_value(value, &target);
+        }
+        Ok(())
+    }
+
+    // functions in the `SOURCES` and `SINKS` namespaces, stubbed so that
+    // programs run without touching the outside world: sources are empty
+    // and whatever is written to a sink is discarded
+    fn builtin(
+        &mut self,
+        path: &str,
+        args: Vec<Value>,
+        span: &Span,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        let empty = || Value::stream(Vec::new());
+        let values = match (path, args.len()) {
+            ("SOURCES::stdin", 0) | ("SOURCES::args", 0) | ("SOURCES::raw_args", 0) => {
+                vec![empty()]
+            }
+            ("SOURCES::tcp", 1) => vec![empty(), empty()],
+            ("SOURCES::file", 1) => vec![Value::Bool(false), empty()],
+            ("SINKS::stdout", 0) | ("SINKS::stderr", 0) | ("SINKS::file", 1) => {
+                vec![empty()]
+            }
+            _ => {
+                return Err(RuntimeError::invalid(
+                    format!(
+                        "cannot find `{}` taking {} argument{}",
+                        path,
+                        args.len(),
+                        if args.len() == 1 { "" } else { "s" }
+                    ),
+                    span,
+                ))
+            }
+        };
+        Ok(values)
+    }
+
+    fn lookup(&self, name: &str, span: &Span) -> Result<Value, RuntimeError> {
+        self.frame
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .cloned()
+            .ok_or_else(|| RuntimeError::invalid(format!("cannot find variable `{}`", name), span))
+    }
+
+    fn declare(&mut self, name: &str, value: Value) {
+        let scope = self.frame.scopes.last_mut().unwrap();
+        scope.insert(name.to_string(), value);
+    }
+
+    fn assign(&mut self, name: &str, value: Value, span: &Span) -> Result<(), RuntimeError> {
+        match self
+            .frame
+            .scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(name))
+        {
+            Some(variable) => {
+                *variable = value;
+                Ok(())
+            }
+            None => Err(RuntimeError::invalid(
+                format!("cannot find variable `{}`", name),
+                span,
+            )),
+        }
+    }
+}
+
+fn write_value(value: Value, target: &Stream) {
+    match value {
+        Value::Stream(source) => {
+            let items: Vec<Value> = source.borrow_mut().drain(..).collect();
+            target.borrow_mut().extend(items);
+        }
+        Value::None => {}
+        value => target.borrow_mut().push_back(value),
+    }
+}
+
+fn method(receiver: Value, name: &str, args: &[Value], span: &Span) -> Result<Value, RuntimeError> {
+    let invalid = |message: String| Err(RuntimeError::invalid(message, span));
+    let stream = match (&receiver, name, args) {
+        (_, "is_some", []) => return Ok(Value::Bool(!matches!(receiver, Value::None))),
+        (_, "is_none", []) => return Ok(Value::Bool(matches!(receiver, Value::None))),
+        (Value::Stream(stream), _, _) => stream,
+        (value, "ascii", []) if value.as_int().is_some() => {
+            let text = value.as_int().unwrap().to_string();
+            return Ok(Value::bytes(text.as_bytes()));
+        }
+        _ => return invalid(format!("no method `{}` for {}", name, receiver)),
+    };
+    let count = |n: &Value| n.as_int().map(|n| n.max(0) as usize);
+    // `next()` and `get(n)` give `none` past the end of the stream
+    let value = match (name, args) {
+        ("next", []) => stream.borrow_mut().pop_front().unwrap_or(Value::None),
+        ("eof", []) => Value::Bool(stream.borrow().is_empty()),
+        ("len", []) => Value::Int(stream.borrow().len() as i64),
+        ("get", []) | ("get", [_]) => {
+            let n = args.first().and_then(count).unwrap_or(0);
+            let item = stream.borrow().get(n).cloned();
+            item.unwrap_or(Value::None)
+        }
+        ("consume", [n]) | ("skip", [n]) if count(n).is_some() => {
+            let mut items = stream.borrow_mut();
+            let n = count(n).unwrap().min(items.len());
+            let consumed: Vec<Value> = items.drain(..n).collect();
+            if name == "consume" {
+                Value::stream(consumed)
+            } else {
+                receiver.clone()
+            }
+        }
+        ("chain", [Value::Stream(other)]) => {
+            let mut items: Vec<Value> = stream.borrow_mut().drain(..).collect();
+            items.extend(other.borrow_mut().drain(..));
+            Value::stream(items)
+        }
+        ("starts_with", [Value::Stream(prefix)]) => {
+            let (items, prefix) = (stream.borrow(), prefix.borrow());
+            Value::Bool(
+                prefix.len() <= items.len() && prefix.iter().zip(items.iter()).all(|(a, b)| a == b),
+            )
+        }
+        ("split", [Value::Stream(delim)]) => {
+            let delim: Vec<Value> = delim.borrow().iter().cloned().collect();
+            let items: Vec<Value> = stream.borrow_mut().drain(..).collect();
+            let mut chunks = Vec::new();
+            let mut chunk = Vec::new();
+            let mut i = 0;
+            while i < items.len() {
+                if !delim.is_empty() && items[i..].starts_with(&delim) {
+                    chunks.push(Value::stream(std::mem::take(&mut chunk)));
+                    i += delim.len();
+                } else {
+                    chunk.push(items[i].clone());
+                    i += 1;
+                }
+            }
+            chunks.push(Value::stream(chunk));
+            Value::stream(chunks)
+        }
+        ("parse", []) => {
+            let n = parse_number(&receiver, span)?;
+            stream.borrow_mut().clear();
+            Value::Int(n)
+        }
+        ("cycle", []) => {
+            return invalid(
+                "`cycle()` makes an infinite stream, which cannot be evaluated eagerly".to_string(),
+            )
+        }
+        _ => {
+            return invalid(format!(
+                "no method `{}` taking {} argument{} for streams",
+                name,
+                args.len(),
+                if args.len() == 1 { "" } else { "s" }
+            ))
+        }
+    };
+    Ok(value)
+}
+
+fn literal(lit: &ast::Lit) -> Value {
+    match lit {
+        ast::Lit::Int {
+            value,
+            suffix: Some(ast::IntSuffix::U8),
+        } => Value::Byte(*value as u8),
+        ast::Lit::Int { value, .. } => Value::Int(*value as i64),
+        ast::Lit::Str(bytes) => Value::bytes(bytes),
+        ast::Lit::Byt(b) => Value::Byte(*b),
+        ast::Lit::Bool(b) => Value::Bool(*b),
+        ast::Lit::None => Value::None,
+    }
+}
+
+// the value of an output before anything is assigned to it
+fn default_value(typ: &ast::Type) -> Value {
+    match typ.name.as_str() {
+        "stream" => Value::stream(Vec::new()),
+        "option" => Value::None,
+        "bool" => Value::Bool(false),
+        "u8" => Value::Byte(0),
+        _ => Value::Int(0),
+    }
+}
+
+fn is_integer(typ: &ast::Type) -> bool {
+    matches!(
+        typ.name.as_str(),
+        "u8" | "u16" | "u32" | "u64" | "i8" | "i16" | "i32" | "i64"
+    )
+}
+
+// a stream of bytes holding a number in decimal
+fn parse_number(value: &Value, span: &Span) -> Result<i64, RuntimeError> {
+    let text = value
+        .to_bytes()
+        .map(|bytes| String::from_utf8_lossy(&bytes).into_owned());
+    match text.as_deref().map(str::parse::<i64>) {
+        Some(Ok(n)) => Ok(n),
+        _ => Err(RuntimeError::invalid(
+            format!("cannot parse {} as a number", value),
+            span,
+        )),
+    }
+}
+
+// integers given to `u8` inputs and outputs become bytes, and bytes given to
+// wider integer types become integers
+fn coerce(value: Value, typ: &ast::Type) -> Value {
+    match (value, typ.name.as_str()) {
+        (Value::Int(n), "u8") => Value::Byte(n as u8),
+        (Value::Byte(b), "u16" | "u32" | "u64" | "i8" | "i16" | "i32" | "i64") => {
+            Value::Int(b as i64)
+        }
+        (value, "option") => match typ.generics.first() {
+            Some(inner) => coerce(value, inner),
+            None => value,
+        },
+        (value, _) => value,
+    }
+}
+
+fn prefix(op: Token, value: Value, span: &Span) -> Result<Value, RuntimeError> {
+    let value = match (op, value) {
+        (T![+], value @ (Value::Int(_) | Value::Byte(_))) => value,
+        (T![-], Value::Int(n)) => Value::Int(n.wrapping_neg()),
+        (T![-], Value::Byte(b)) => Value::Byte(b.wrapping_neg()),
+        (T![~], Value::Int(n)) => Value::Int(!n),
+        (T![~], Value::Byte(b)) => Value::Byte(!b),
+        (T![!], Value::Bool(b)) => Value::Bool(!b),
+        (op, value) => {
+            return Err(RuntimeError::invalid(
+                format!("cannot apply `{}` to {}", op, value),
+                span,
+            ))
+        }
+    };
+    Ok(value)
+}
+
+// arithmetic wraps around, and stays within a byte when either side is one
+fn binary(op: Token, lhs: Value, rhs: Value, span: &Span) -> Result<Value, RuntimeError> {
+    let invalid = |message: String| Err(RuntimeError::invalid(message, span));
+    match (op, &lhs, &rhs) {
+        (T![==], _, _) => return Ok(Value::Bool(lhs == rhs)),
+        (T![!=], _, _) => return Ok(Value::Bool(lhs != rhs)),
+        (T![&], Value::Bool(a), Value::Bool(b)) => return Ok(Value::Bool(a & b)),
+        (T![|], Value::Bool(a), Value::Bool(b)) => return Ok(Value::Bool(a | b)),
+        (T![^], Value::Bool(a), Value::Bool(b)) => return Ok(Value::Bool(a ^ b)),
+        _ => {}
+    }
+    let (a, b) = match (lhs.as_int(), rhs.as_int()) {
+        (Some(a), Some(b)) => (a, b),
+        _ => return invalid(format!("cannot apply `{}` to {} and {}", op, lhs, rhs)),
+    };
+    let n = match op {
+        T![<] => return Ok(Value::Bool(a < b)),
+        T![<=] => return Ok(Value::Bool(a <= b)),
+        T![>] => return Ok(Value::Bool(a > b)),
+        T![>=] => return Ok(Value::Bool(a >= b)),
+        T![/] | T![%] if b == 0 => return invalid("attempt to divide by zero".to_string()),
+        T![+] => a.wrapping_add(b),
+        T![-] => a.wrapping_sub(b),
+        T![*] => a.wrapping_mul(b),
+        T![/] => a.wrapping_div(b),
+        T![%] => a.wrapping_rem(b),
+        T![&] => a & b,
+        T![|] => a | b,
+        T![^] => a ^ b,
+        T![<<] => a.wrapping_shl(b as u32),
+        T![>>] => a.wrapping_shr(b as u32),
+        _ => return invalid(format!("cannot apply `{}` to {} and {}", op, lhs, rhs)),
+    };
+    if matches!(lhs, Value::Byte(_)) || matches!(rhs, Value::Byte(_)) {
+        Ok(Value::Byte(n as u8))
+    } else {
+        Ok(Value::Int(n))
+    }
+}
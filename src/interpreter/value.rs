@@ -0,0 +1,132 @@
+use crate::parser::ast::Lit;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt::{Display, Error, Formatter};
+use std::rc::Rc;
+
+// streams are shared: every variable bound to a stream reads from and writes
+// to the same buffer, and reading an item consumes it
+pub type Stream = Rc<RefCell<VecDeque<Value>>>;
+
+// an `option<T>` holding a value is the value itself, so only `none` needs a
+// variant of its own
+#[derive(Debug, Clone)]
+pub enum Value {
+    Int(i64),
+    Byte(u8),
+    Bool(bool),
+    Stream(Stream),
+    None,
+}
+
+impl Value {
+    pub fn stream<T: IntoIterator<Item = Value>>(items: T) -> Self {
+        Value::Stream(Rc::new(RefCell::new(items.into_iter().collect())))
+    }
+
+    pub fn bytes(bytes: &[u8]) -> Self {
+        Value::stream(bytes.iter().map(|b| Value::Byte(*b)))
+    }
+
+    // the contents of a stream of bytes
+    pub fn to_bytes(&self) -> Option<Vec<u8>> {
+        match self {
+            Value::Stream(stream) => stream
+                .borrow()
+                .iter()
+                .map(|item| match item {
+                    Value::Byte(b) => Some(*b),
+                    _ => None,
+                })
+                .collect(),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Value::Int(n) => Some(*n),
+            Value::Byte(b) => Some(*b as i64),
+            _ => None,
+        }
+    }
+}
+
+// streams are compared item by item without consuming them; bytes and
+// integers are compared by value
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Stream(a), Value::Stream(b)) => *a.borrow() == *b.borrow(),
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::None, Value::None) => true,
+            (a, b) => match (a.as_int(), b.as_int()) {
+                (Some(a), Some(b)) => a == b,
+                _ => false,
+            },
+        }
+    }
+}
+
+// the index of the first item where two streams differ, counting the end of
+// the shorter stream as a difference
+pub fn first_difference(a: &Value, b: &Value) -> Option<usize> {
+    let (a, b) = match (a, b) {
+        (Value::Stream(a), Value::Stream(b)) => (a.borrow(), b.borrow()),
+        _ => return None,
+    };
+    (0..a.len().max(b.len())).find(|&i| a.get(i) != b.get(i))
+}
+
+// values are shown the way they would be written in a program, so a stream
+// of bytes looks like a string literal
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match self {
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Byte(b) => write!(f, "{}", Lit::Byt(*b)),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::None => write!(f, "none"),
+            Value::Stream(stream) => match self.to_bytes() {
+                Some(bytes) if !bytes.is_empty() => write!(f, "{}", Lit::Str(bytes)),
+                _ => {
+                    let items: Vec<String> = stream
+                        .borrow()
+                        .iter()
+                        .map(|item| item.to_string())
+                        .collect();
+                    write!(f, "[{}]", items.join(", "))
+                }
+            },
+        }
+    }
+}
+
+#[test]
+fn display() {
+    assert_eq!(Value::Int(-3).to_string(), "-3");
+    assert_eq!(Value::Byte(b'a').to_string(), "`a`");
+    assert_eq!(Value::bytes(b"foo\n").to_string(), "\"foo\\n\"");
+    assert_eq!(
+        Value::stream(vec![Value::Int(1), Value::Int(2)]).to_string(),
+        "[1, 2]"
+    );
+    assert_eq!(Value::stream(vec![]).to_string(), "[]");
+    assert_eq!(
+        Value::stream(vec![Value::Int(1), Value::None]).to_string(),
+        "[1, none]"
+    );
+}
+
+#[test]
+fn differences() {
+    let diff = first_difference(&Value::bytes(b"sbc"), &Value::bytes(b"sbb"));
+    assert_eq!(diff, Some(2));
+    let diff = first_difference(&Value::bytes(b"foo"), &Value::bytes(b"foobar"));
+    assert_eq!(diff, Some(3));
+    assert_eq!(
+        first_difference(&Value::bytes(b"foo"), &Value::bytes(b"foo")),
+        None
+    );
+    assert_eq!(first_difference(&Value::Int(1), &Value::Int(2)), None);
+}
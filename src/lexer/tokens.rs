@@ -9,11 +9,13 @@ pub enum Token {
 
     #[regex("[a-zA-Z][a-zA-Z0-9_]*")]
     Ident,
-    #[regex("[0-9_]+", |lex| lex.slice().replace('_', "").parse())]
-    Number(usize),
-    #[regex(r#""([^"]|\.)*"|'([^']|\.)*'"#)]
+    // the digits, radix prefix and type suffix are checked by the parser so
+    // it can report precisely what is wrong with a literal
+    #[regex("[0-9][0-9a-zA-Z_]*")]
+    Number,
+    #[regex(r#""([^"\\]|\\.)*"|'([^'\\]|\\.)*'"#)]
     String,
-    #[regex(r#"`([^\\]|\.)`"#)]
+    #[regex(r#"`([^`\\]|\\.)*`"#)]
     Byte,
     #[token("_")]
     Underscore,
@@ -45,6 +47,8 @@ pub enum Token {
     Tilde,
     #[token("?")]
     Question,
+    #[token("#")]
+    Hash,
     #[token(",")]
     Comma,
     #[token(";")]
@@ -61,6 +65,26 @@ pub enum Token {
     Assign,
     #[token(":=")]
     Declare,
+    #[token("+=")]
+    PlusAssign,
+    #[token("-=")]
+    MinusAssign,
+    #[token("*=")]
+    AsteriskAssign,
+    #[token("/=")]
+    SlashAssign,
+    #[token("%=")]
+    PercentAssign,
+    #[token("&=")]
+    AmpersandAssign,
+    #[token("|=")]
+    PipeAssign,
+    #[token("^=")]
+    CaretAssign,
+    #[token("<<=")]
+    ShiftLeftAssign,
+    #[token(">>=")]
+    ShiftRightAssign,
     #[token("{")]
     LeftCurlyBracket,
     #[token("}")]
@@ -85,10 +109,20 @@ pub enum Token {
     GreaterOrEqual,
     #[token("<=")]
     LessOrEqual,
+    #[token("<<")]
+    ShiftLeft,
+    // also closes two generic lists, as in `stream<stream<u8>>`; the parser
+    // splits it there
+    #[token(">>")]
+    ShiftRight,
     #[regex("//[^\n]*", logos::skip)]
     Comment,
     #[token(".")]
     Dot,
+    #[token("..")]
+    DotDot,
+    #[token("assert")]
+    KeywordAssert,
     #[token("break")]
     KeywordBreak,
     #[token("continue")]
@@ -124,7 +158,7 @@ impl Display for Token {
             Token::Error => write!(f, "error"),
             // Token::Whitespace => write!(f, "<ws>"),
             Token::Ident => write!(f, "ident"),
-            Token::Number(n) => write!(f, "num({})", n),
+            Token::Number => write!(f, "number"),
             Token::String => write!(f, "string"),
             Token::Byte => write!(f, "byte"),
             Token::Underscore => write!(f, "_"),
@@ -142,6 +176,7 @@ impl Display for Token {
             Token::Bang => write!(f, "!"),
             Token::Tilde => write!(f, "~"),
             Token::Question => write!(f, "?"),
+            Token::Hash => write!(f, "#"),
             Token::Comma => write!(f, ","),
             Token::Semicolon => write!(f, ";"),
             Token::Colon => write!(f, ":"),
@@ -150,6 +185,16 @@ impl Display for Token {
             Token::Ampersand => write!(f, "&"),
             Token::Assign => write!(f, "="),
             Token::Declare => write!(f, ":="),
+            Token::PlusAssign => write!(f, "+="),
+            Token::MinusAssign => write!(f, "-="),
+            Token::AsteriskAssign => write!(f, "*="),
+            Token::SlashAssign => write!(f, "/="),
+            Token::PercentAssign => write!(f, "%="),
+            Token::AmpersandAssign => write!(f, "&="),
+            Token::PipeAssign => write!(f, "|="),
+            Token::CaretAssign => write!(f, "^="),
+            Token::ShiftLeftAssign => write!(f, "<<="),
+            Token::ShiftRightAssign => write!(f, ">>="),
             Token::LeftCurlyBracket => write!(f, "{{"),
             Token::RightCurlyBracket => write!(f, "}}"),
             Token::LeftParen => write!(f, "("),
@@ -162,8 +207,12 @@ impl Display for Token {
             Token::NotEqual => write!(f, "!="),
             Token::GreaterOrEqual => write!(f, ">="),
             Token::LessOrEqual => write!(f, "<="),
+            Token::ShiftLeft => write!(f, "<<"),
+            Token::ShiftRight => write!(f, ">>"),
             Token::Comment => write!(f, "// comment"),
             Token::Dot => write!(f, "."),
+            Token::DotDot => write!(f, ".."),
+            Token::KeywordAssert => write!(f, "assert"),
             Token::KeywordBreak => write!(f, "break"),
             Token::KeywordContinue => write!(f, "continue"),
             Token::KeywordElse => write!(f, "else"),
@@ -186,7 +235,7 @@ macro_rules! T {
     [error] => { $crate::lexer::Token::Error };
     // [ws] => { $crate::lexer::Token::Whitespace };
     [ident] => { $crate::lexer::Token::Ident };
-    [num($i:pat)] => { $crate::lexer::Token::Number($i) };
+    [num] => { $crate::lexer::Token::Number };
     [string] => { $crate::lexer::Token::String };
     [byte] => { $crate::lexer::Token::Byte };
     [_] => { $crate::lexer::Token::Underscore };
@@ -204,6 +253,7 @@ macro_rules! T {
     [!] => { $crate::lexer::Token::Bang };
     [~] => { $crate::lexer::Token::Tilde };
     [?] => { $crate::lexer::Token::Question };
+    [#] => { $crate::lexer::Token::Hash };
     [,] => { $crate::lexer::Token::Comma };
     [;] => { $crate::lexer::Token::Semicolon };
     [:] => { $crate::lexer::Token::Colon };
@@ -212,6 +262,16 @@ macro_rules! T {
     [&] => { $crate::lexer::Token::Ampersand };
     [=] => { $crate::lexer::Token::Assign };
     [:=] => { $crate::lexer::Token::Declare };
+    [+=] => { $crate::lexer::Token::PlusAssign };
+    [-=] => { $crate::lexer::Token::MinusAssign };
+    [*=] => { $crate::lexer::Token::AsteriskAssign };
+    [/=] => { $crate::lexer::Token::SlashAssign };
+    [%=] => { $crate::lexer::Token::PercentAssign };
+    [&=] => { $crate::lexer::Token::AmpersandAssign };
+    [|=] => { $crate::lexer::Token::PipeAssign };
+    [^=] => { $crate::lexer::Token::CaretAssign };
+    [<<=] => { $crate::lexer::Token::ShiftLeftAssign };
+    [>>=] => { $crate::lexer::Token::ShiftRightAssign };
     ['{'] => { $crate::lexer::Token::LeftCurlyBracket };
     ['}'] => { $crate::lexer::Token::RightCurlyBracket };
     ['('] => { $crate::lexer::Token::LeftParen };
@@ -224,8 +284,12 @@ macro_rules! T {
     [!=] => { $crate::lexer::Token::NotEqual };
     [>=] => { $crate::lexer::Token::GreaterOrEqual };
     [<=] => { $crate::lexer::Token::LessOrEqual };
+    [<<] => { $crate::lexer::Token::ShiftLeft };
+    [>>] => { $crate::lexer::Token::ShiftRight };
     [comment] => { $crate::lexer::Token::Comment };
     [.] => { $crate::lexer::Token::Dot };
+    [..] => { $crate::lexer::Token::DotDot };
+    [assert] => { $crate::lexer::Token::KeywordAssert };
     [break] => { $crate::lexer::Token::KeywordBreak };
     [continue] => { $crate::lexer::Token::KeywordContinue };
     [else] => { $crate::lexer::Token::KeywordElse };
@@ -1,17 +1,56 @@
 pub mod checker;
+pub mod desugar;
+pub mod diagnostic;
+pub mod interpreter;
 pub mod lexer;
 pub mod parser;
+pub mod span;
+pub mod testing;
 
+use diagnostic::Diagnostic;
 use std::fs;
 
-fn compile(program: &str) {
+// parse and check a program, returning every problem found
+// - the checker only runs once the program parses without errors
+pub fn compile(program: &str) -> Result<(), Vec<Diagnostic>> {
+    check(program).map(|_| ())
+}
+
+// like `compile`, but keeps the checked items
+pub fn check(program: &str) -> Result<Vec<parser::ast::Item>, Vec<Diagnostic>> {
+    check_items(program, false)
+}
+
+// like `check`, for a program that is run for its tests and so may have no
+// `main`
+pub fn check_tests(program: &str) -> Result<Vec<parser::ast::Item>, Vec<Diagnostic>> {
+    check_items(program, true)
+}
+
+fn check_items(program: &str, tests: bool) -> Result<Vec<parser::ast::Item>, Vec<Diagnostic>> {
     let mut parser = parser::Parser::new(program);
-    let items = parser.file();
+    let items = parser
+        .file()
+        .map_err(|errors| errors.iter().map(Diagnostic::from).collect::<Vec<_>>())?;
     let mut checker = checker::Checker::new(&items);
-    checker.check_all();
+    if tests {
+        checker = checker.for_tests();
+    }
+    checker
+        .check_all()
+        .map_err(|errors| errors.iter().map(Diagnostic::from).collect::<Vec<_>>())?;
+    Ok(items)
 }
 
-pub fn compile_file(input_filename: &str) {
-    let program = fs::read_to_string(input_filename).unwrap();
-    compile(&program);
+pub fn compile_file(input_filename: &str) -> Result<(), Vec<Diagnostic>> {
+    let program = fs::read_to_string(input_filename).map_err(|err| {
+        vec![Diagnostic {
+            code: None,
+            message: format!("could not read `{}`: {}", input_filename, err),
+            span: None,
+            labels: Vec::new(),
+            notes: Vec::new(),
+        }]
+    })?;
+    compile(&program)
 }
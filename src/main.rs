@@ -1,12 +1,102 @@
-use burn;
-use std::env;
+use burn::diagnostic::Diagnostic;
+use burn::testing::{run_tests, Outcome};
+use std::{env, fs};
+
+const USAGE: &str = "usage: burn <file>\n       burn test <file> [filter]";
 
 fn main() {
     let args: Vec<_> = env::args().skip(1).collect();
-    if args.len() != 1 {
-        eprintln!("Expected one filename argument");
-        std::process::exit(1);
+    match &args[..] {
+        [filename] => check(filename),
+        [command, filename] if command == "test" => test(filename, ""),
+        [command, filename, filter] if command == "test" => test(filename, filter),
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn read(filename: &str) -> String {
+    match fs::read_to_string(filename) {
+        Ok(program) => program,
+        Err(err) => {
+            eprintln!("error: could not read `{}`: {}", filename, err);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn report_errors(filename: &str, program: &str, diagnostics: &[Diagnostic]) -> ! {
+    for diagnostic in diagnostics {
+        eprintln!("{}", diagnostic.render(filename, program));
+    }
+    eprintln!(
+        "error: could not compile `{}` due to {} previous error{}",
+        filename,
+        diagnostics.len(),
+        if diagnostics.len() == 1 { "" } else { "s" }
+    );
+    std::process::exit(1);
+}
+
+fn check(filename: &str) {
+    let program = read(filename);
+    if let Err(diagnostics) = burn::compile(&program) {
+        report_errors(filename, &program, &diagnostics);
     }
-    burn::compile_file(&args[0]); // will panic if it cannot compile
     println!("[+] All checks passed");
 }
+
+// prints results in the same layout as `cargo test`
+fn test(filename: &str, filter: &str) {
+    let program = read(filename);
+    let report = match run_tests(&program, filter) {
+        Ok(report) => report,
+        Err(diagnostics) => report_errors(filename, &program, &diagnostics),
+    };
+    let count = report.results.len();
+    println!(
+        "\nrunning {} test{}",
+        count,
+        if count == 1 { "" } else { "s" }
+    );
+    for (name, outcome) in &report.results {
+        let status = match outcome {
+            Outcome::Passed => "ok",
+            Outcome::Failed(_) => "FAILED",
+            Outcome::Ignored => "ignored",
+        };
+        println!("test {} ... {}", name, status);
+    }
+    let failures: Vec<_> = report
+        .results
+        .iter()
+        .filter_map(|(name, outcome)| match outcome {
+            Outcome::Failed(err) => Some((name, err)),
+            _ => None,
+        })
+        .collect();
+    if !failures.is_empty() {
+        println!("\nfailures:\n");
+        for (name, err) in &failures {
+            println!("---- {} ----", name);
+            println!("{}", Diagnostic::from(*err).render(filename, &program));
+        }
+        println!("failures:");
+        for (name, _) in &failures {
+            println!("    {}", name);
+        }
+    }
+    println!(
+        "\ntest result: {}. {} passed; {} failed; {} ignored; {} filtered out\n",
+        if report.passed() { "ok" } else { "FAILED" },
+        report.count(|o| matches!(o, Outcome::Passed)),
+        failures.len(),
+        report.count(|o| matches!(o, Outcome::Ignored)),
+        report.filtered_out
+    );
+    if !report.passed() {
+        std::process::exit(1);
+    }
+}
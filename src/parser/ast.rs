@@ -1,13 +1,40 @@
 use crate::lexer::Token;
+pub use crate::span::Span;
+
+// Every node keeps the byte range it was parsed from. Spans are not part of a
+// node's identity, so two nodes compare equal when their contents are equal
+// regardless of where they came from.
+
+#[derive(Debug, Clone)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
 
 #[derive(Debug, Clone, PartialEq)]
-pub enum Expr {
+pub enum ExprKind {
     Literal(Lit),
     Ident(String),
     FnCall {
         fn_name: String,
         args: Vec<Expr>,
     },
+    // `a::b::c`, a name inside one or more namespaces
+    Path {
+        segments: Vec<String>,
+    },
+    // `a::b(args)`, a call to a function inside a namespace, as in
+    // `SOURCES::stdin()`; `path` is the `Path` of the function
+    PathCall {
+        path: Box<Expr>,
+        args: Vec<Expr>,
+    },
+    // `receiver.method(args)`
+    MethodCall {
+        receiver: Box<Expr>,
+        method: String,
+        args: Vec<Expr>,
+    },
     PrefixOp {
         op: Token,
         expr: Box<Expr>,
@@ -21,104 +48,571 @@ pub enum Expr {
         op: Token,
         expr: Box<Expr>,
     },
+    // `expr[index]`, where the index may be a range to take a sub-stream
+    Index {
+        expr: Box<Expr>,
+        index: Box<Expr>,
+    },
+    // `start..end`, either bound may be left out; only used as an index
+    Range {
+        start: Option<Box<Expr>>,
+        end: Option<Box<Expr>>,
+    },
+    // `value -> call`, which feeds the value into the call; see `desugar`
+    Pipe {
+        value: Box<Expr>,
+        call: Box<Expr>,
+    },
     Placeholder,
     Tuple(Vec<Expr>),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Lit {
-    Int(usize),
-    Str(String),
-    Byt(String),
+    Int {
+        value: u64,
+        suffix: Option<IntSuffix>,
+    },
+    // string and byte literals hold their decoded bytes
+    Str(Vec<u8>),
+    Byt(u8),
+    Bool(bool),
+    // the absence of a value in an `option<T>`
+    None,
+}
+
+// type given explicitly to an integer literal, as in `255u8`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IntSuffix {
+    U8,
+    U16,
+    U32,
+    U64,
+    I8,
+    I16,
+    I32,
+    I64,
+}
+
+impl IntSuffix {
+    pub fn from_name(name: &str) -> Option<Self> {
+        let suffix = match name {
+            "u8" => IntSuffix::U8,
+            "u16" => IntSuffix::U16,
+            "u32" => IntSuffix::U32,
+            "u64" => IntSuffix::U64,
+            "i8" => IntSuffix::I8,
+            "i16" => IntSuffix::I16,
+            "i32" => IntSuffix::I32,
+            "i64" => IntSuffix::I64,
+            _ => return None,
+        };
+        Some(suffix)
+    }
+    pub fn name(&self) -> &'static str {
+        match self {
+            IntSuffix::U8 => "u8",
+            IntSuffix::U16 => "u16",
+            IntSuffix::U32 => "u32",
+            IntSuffix::U64 => "u64",
+            IntSuffix::I8 => "i8",
+            IntSuffix::I16 => "i16",
+            IntSuffix::I32 => "i32",
+            IntSuffix::I64 => "i64",
+        }
+    }
+    pub fn is_signed(&self) -> bool {
+        matches!(
+            self,
+            IntSuffix::I8 | IntSuffix::I16 | IntSuffix::I32 | IntSuffix::I64
+        )
+    }
+    // largest magnitude a literal of this type can have; signed types allow
+    // one more when the literal is negated
+    pub fn max(&self, negated: bool) -> u64 {
+        let bits = match self {
+            IntSuffix::U8 | IntSuffix::I8 => 8,
+            IntSuffix::U16 | IntSuffix::I16 => 16,
+            IntSuffix::U32 | IntSuffix::I32 => 32,
+            IntSuffix::U64 | IntSuffix::I64 => 64,
+        };
+        match (self.is_signed(), negated) {
+            (false, _) => u64::MAX >> (64 - bits),
+            (true, false) => (1 << (bits - 1)) - 1,
+            (true, true) => 1 << (bits - 1),
+        }
+    }
+    // `min..=max`, as shown in errors about literals that do not fit
+    pub fn range(&self) -> String {
+        let min = if self.is_signed() {
+            format!("-{}", self.max(true))
+        } else {
+            "0".to_string()
+        };
+        format!("{}..={}", min, self.max(false))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Stmt {
+    pub kind: StmtKind,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub enum Stmt {
+pub enum StmtKind {
     Declaration {
-        var_names: Vec<String>,
+        var_names: Vec<Name>,
         value: Expr,
     },
     Assignment {
-        var_names: Vec<String>,
+        var_names: Vec<Name>,
+        value: Expr,
+    },
+    // `x += value` and friends; `op` is the binary operator, e.g. `+`
+    CompoundAssignment {
+        var_name: Name,
+        op: Token,
         value: Expr,
     },
+    Increment {
+        var_name: Name,
+    },
+    Decrement {
+        var_name: Name,
+    },
     IfStmt {
         condition: Expr,
         body: Vec<Stmt>,
         else_stmt: Option<Box<Stmt>>,
     },
+    // values are assigned to the function's outputs in order, where `_`
+    // leaves an output as it is; a bare `return` assigns nothing
     ReturnStmt {
-        value: Option<Expr>,
+        values: Vec<Expr>,
     },
     ContinueStmt,
+    BreakStmt,
     ForLoop {
-        var_name: String,
+        var_name: Name,
         stream: Expr,
         stmts: Vec<Stmt>,
     },
+    WhileLoop {
+        condition: Expr,
+        stmts: Vec<Stmt>,
+    },
     Block {
         stmts: Vec<Stmt>,
     },
+    Assert {
+        condition: Expr,
+        message: Option<Expr>,
+    },
     Expr(Expr),
+    // `value -> stream;` appends the value to the stream, and
+    // `values -> (a, b);` appends each of several values to its own stream
+    StreamWrite {
+        value: Expr,
+        stream: Expr,
+    },
+    // a statement that failed to parse
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct Item {
+    pub attributes: Vec<Attribute>,
+    pub kind: ItemKind,
+    pub span: Span,
+}
+
+// `#[name]` or `#[name(args)]` written before an item
+#[derive(Debug, Clone)]
+pub struct Attribute {
+    pub name: String,
+    pub args: Vec<Expr>,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub enum Item {
+pub enum ItemKind {
     Function {
         name: String,
-        parameters: Vec<(String, Type)>,
+        parameters: Vec<Param>,
         body: Vec<Stmt>,
-        return_params: Vec<(String, Type)>,
+        return_params: Vec<Param>,
     },
+    // an item that failed to parse
+    Error,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+// `name type`, an input or output of a function; the span is the name's
+#[derive(Debug, Clone)]
+pub struct Param {
+    pub name: String,
+    pub typ: Type,
+    pub span: Span,
+}
+
+// a variable named by a statement, as in `x := 1;`; the span is the name's
+#[derive(Debug, Clone)]
+pub struct Name {
+    pub name: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct Type {
     pub name: String,
     pub generics: Vec<Type>,
+    pub span: Span,
+}
+
+impl Expr {
+    pub fn new(kind: ExprKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+}
+
+impl Stmt {
+    pub fn new(kind: StmtKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+}
+
+impl Item {
+    pub fn new(kind: ItemKind, span: Span) -> Self {
+        Self {
+            attributes: Vec::new(),
+            kind,
+            span,
+        }
+    }
+    pub fn has_attribute(&self, name: &str) -> bool {
+        self.attributes.iter().any(|attr| attr.name == name)
+    }
+}
+
+impl Attribute {
+    pub fn new(name: &str, args: Vec<Expr>) -> Self {
+        Self {
+            name: name.to_string(),
+            args,
+            span: Span::default(),
+        }
+    }
+}
+
+impl Param {
+    pub fn new(name: &str, typ: Type) -> Self {
+        Self {
+            name: name.to_string(),
+            typ,
+            span: Span::default(),
+        }
+    }
+}
+
+impl Name {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            span: Span::default(),
+        }
+    }
+}
+
+impl Type {
+    pub fn new(name: &str, generics: Vec<Type>) -> Self {
+        Self {
+            name: name.to_string(),
+            generics,
+            span: Span::default(),
+        }
+    }
+
+    // the type of several values at once, such as the outputs of a call;
+    // there is no way to write it in source, so it is only made by the
+    // checker
+    pub fn tuple(items: Vec<Type>) -> Self {
+        Self::new("()", items)
+    }
+
+    pub fn is_tuple(&self) -> bool {
+        self.name == "()"
+    }
+}
+
+// Nodes built outside the parser (in tests or by rewriting passes) have no
+// location in the source, so they get an empty span.
+impl From<ExprKind> for Expr {
+    fn from(kind: ExprKind) -> Self {
+        Self::new(kind, Span::default())
+    }
+}
+
+impl From<StmtKind> for Stmt {
+    fn from(kind: StmtKind) -> Self {
+        Self::new(kind, Span::default())
+    }
+}
+
+impl From<ItemKind> for Item {
+    fn from(kind: ItemKind) -> Self {
+        Self::new(kind, Span::default())
+    }
+}
+
+impl PartialEq for Expr {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+    }
+}
+
+impl PartialEq for Stmt {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+    }
+}
+
+impl PartialEq for Item {
+    fn eq(&self, other: &Self) -> bool {
+        self.attributes == other.attributes && self.kind == other.kind
+    }
+}
+
+impl PartialEq for Attribute {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.args == other.args
+    }
+}
+
+impl PartialEq for Param {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.typ == other.typ
+    }
+}
+
+impl PartialEq for Name {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl PartialEq for Type {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.generics == other.generics
+    }
 }
 
+use super::literals::escape;
 use std::fmt::{Display, Error, Formatter};
 impl Display for Expr {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
-        match self {
-            Expr::Literal(lit) => write!(f, "{}", lit),
-            Expr::Ident(ident) => write!(f, "{}", ident),
-            Expr::FnCall { fn_name, args } => write!(
-                f,
-                "{}({})",
-                fn_name,
-                args.into_iter()
-                    .map(|e| e.to_string())
-                    .fold(String::new(), |s, e| {
-                        if s.len() == 0 {
-                            e
-                        } else {
-                            s + ", " + &e
-                        }
-                    })
-            ),
-            Expr::PrefixOp { op, expr } => write!(f, "({}{})", op, expr),
-            Expr::InfixOp { op, lhs, rhs } => write!(f, "({} {} {})", lhs, op, rhs),
-            Expr::PostfixOp { op, expr } => write!(f, "({}{})", expr, op),
-            Expr::Placeholder => write!(f, "_"),
-            Expr::Tuple(items) => {
-                write!(f, "(")?;
-                for item in items {
-                    write!(f, "{}", item)?;
+        match &self.kind {
+            ExprKind::Literal(lit) => write!(f, "{}", lit),
+            ExprKind::Ident(ident) => write!(f, "{}", ident),
+            ExprKind::FnCall { fn_name, args } => write!(f, "{}({})", fn_name, join(args)),
+            ExprKind::Path { segments } => write!(f, "{}", segments.join("::")),
+            ExprKind::PathCall { path, args } => write!(f, "{}({})", path, join(args)),
+            ExprKind::MethodCall {
+                receiver,
+                method,
+                args,
+            } => write!(f, "{}.{}({})", receiver, method, join(args)),
+            ExprKind::PrefixOp { op, expr } => write!(f, "({}{})", op, expr),
+            ExprKind::InfixOp { op, lhs, rhs } => write!(f, "({} {} {})", lhs, op, rhs),
+            ExprKind::PostfixOp { op, expr } => write!(f, "({}{})", expr, op),
+            ExprKind::Index { expr, index } => write!(f, "({}[{}])", expr, index),
+            ExprKind::Range { start, end } => {
+                if let Some(start) = start {
+                    write!(f, "{}", start)?;
+                }
+                write!(f, "..")?;
+                if let Some(end) = end {
+                    write!(f, "{}", end)?;
+                }
+                Ok(())
+            }
+            ExprKind::Pipe { value, call } => write!(f, "({} -> {})", value, call),
+            ExprKind::Placeholder => write!(f, "_"),
+            ExprKind::Tuple(items) => write!(f, "({})", join(items)),
+        }
+    }
+}
+
+fn join(exprs: &[Expr]) -> String {
+    exprs
+        .iter()
+        .map(|e| e.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl Display for Name {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "{}", self.name)
+    }
+}
+
+fn join_names(names: &[Name]) -> String {
+    names
+        .iter()
+        .map(|n| n.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl Display for Stmt {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match &self.kind {
+            StmtKind::Declaration { var_names, value } => {
+                write!(f, "{} := {};", join_names(var_names), value)
+            }
+            StmtKind::Assignment { var_names, value } => {
+                write!(f, "{} = {};", join_names(var_names), value)
+            }
+            StmtKind::CompoundAssignment {
+                var_name,
+                op,
+                value,
+            } => write!(f, "{} {}= {};", var_name, op, value),
+            StmtKind::Increment { var_name } => write!(f, "{}++;", var_name),
+            StmtKind::Decrement { var_name } => write!(f, "{}--;", var_name),
+            StmtKind::IfStmt {
+                condition,
+                body,
+                else_stmt,
+            } => {
+                write!(f, "if {} ", condition)?;
+                write_block(f, body)?;
+                match else_stmt {
+                    Some(stmt) => write!(f, " else {}", stmt),
+                    None => Ok(()),
+                }
+            }
+            StmtKind::ReturnStmt { values } if values.is_empty() => write!(f, "return;"),
+            StmtKind::ReturnStmt { values } => {
+                let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                write!(f, "return {};", values.join(", "))
+            }
+            StmtKind::ContinueStmt => write!(f, "continue;"),
+            StmtKind::BreakStmt => write!(f, "break;"),
+            StmtKind::ForLoop {
+                var_name,
+                stream,
+                stmts,
+            } => {
+                write!(f, "for {} in {} ", var_name, stream)?;
+                write_block(f, stmts)
+            }
+            StmtKind::WhileLoop { condition, stmts } => {
+                write!(f, "while {} ", condition)?;
+                write_block(f, stmts)
+            }
+            StmtKind::Block { stmts } => write_block(f, stmts),
+            StmtKind::Assert {
+                condition,
+                message: Some(message),
+            } => write!(f, "assert {}, {};", condition, message),
+            StmtKind::Assert {
+                condition,
+                message: None,
+            } => write!(f, "assert {};", condition),
+            StmtKind::Expr(expr) => write!(f, "{};", expr),
+            StmtKind::StreamWrite { value, stream } => write!(f, "{} -> {};", value, stream),
+            StmtKind::Error => write!(f, "<error>;"),
+        }
+    }
+}
+
+// statements surrounded by curly brackets, one per line and indented
+fn write_block(f: &mut Formatter, stmts: &[Stmt]) -> Result<(), Error> {
+    if stmts.is_empty() {
+        return write!(f, "{{}}");
+    }
+    writeln!(f, "{{")?;
+    for stmt in stmts {
+        for line in stmt.to_string().lines() {
+            writeln!(f, "    {}", line)?;
+        }
+    }
+    write!(f, "}}")
+}
+
+// functions are always shown with `->` in front of their outputs
+impl Display for Item {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        for attribute in &self.attributes {
+            writeln!(f, "{}", attribute)?;
+        }
+        match &self.kind {
+            ItemKind::Function {
+                name,
+                parameters,
+                body,
+                return_params,
+            } => {
+                write!(f, "fn {}({}) ", name, join_params(parameters))?;
+                if !return_params.is_empty() {
+                    write!(f, "-> ({}) ", join_params(return_params))?;
                 }
-                write!(f, ")")
+                write_block(f, body)
             }
+            ItemKind::Error => write!(f, "<error>"),
+        }
+    }
+}
+
+fn join_params(params: &[Param]) -> String {
+    params
+        .iter()
+        .map(|param| format!("{} {}", param.name, param.typ))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        if self.is_tuple() {
+            let items: Vec<String> = self.generics.iter().map(|g| g.to_string()).collect();
+            return write!(f, "({})", items.join(", "));
+        }
+        write!(f, "{}", self.name)?;
+        if !self.generics.is_empty() {
+            let generics: Vec<String> = self.generics.iter().map(|g| g.to_string()).collect();
+            write!(f, "<{}>", generics.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Attribute {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "#[{}", self.name)?;
+        if !self.args.is_empty() {
+            let args: Vec<String> = self.args.iter().map(|arg| arg.to_string()).collect();
+            write!(f, "({})", args.join(", "))?;
         }
+        write!(f, "]")
     }
 }
 
 impl Display for Lit {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
         match self {
-            Lit::Int(n) => write!(f, "{}", n),
-            Lit::Str(s) => write!(f, "\"{}\"", s),
-            Lit::Byt(s) => write!(f, "`{}`", s),
+            Lit::Int { value, suffix } => {
+                write!(f, "{}", value)?;
+                match suffix {
+                    Some(suffix) => write!(f, "{}", suffix.name()),
+                    None => Ok(()),
+                }
+            }
+            Lit::Str(s) => write!(f, "\"{}\"", escape(s, b'"')),
+            Lit::Byt(b) => write!(f, "`{}`", escape(&[*b], b'`')),
+            Lit::Bool(b) => write!(f, "{}", b),
+            Lit::None => write!(f, "none"),
         }
     }
 }
@@ -127,19 +621,86 @@ impl Display for Lit {
 fn display() {
     use crate::T;
 
-    assert_eq!(Expr::Literal(Lit::Int(1)).to_string(), "1");
     assert_eq!(
-        Expr::Literal(Lit::Str("foo".to_string())).to_string(),
+        Expr::from(ExprKind::Literal(Lit::Int {
+            value: 1,
+            suffix: None
+        }))
+        .to_string(),
+        "1"
+    );
+    assert_eq!(
+        Expr::from(ExprKind::Literal(Lit::Str(b"foo".to_vec()))).to_string(),
         "\"foo\""
     );
     assert_eq!(
-        Expr::InfixOp {
+        Expr::from(ExprKind::InfixOp {
             op: T![+],
-            lhs: Box::new(Expr::Literal(Lit::Int(1))),
-            rhs: Box::new(Expr::Literal(Lit::Int(2))),
-        }
+            lhs: Box::new(
+                ExprKind::Literal(Lit::Int {
+                    value: 1,
+                    suffix: None
+                })
+                .into()
+            ),
+            rhs: Box::new(
+                ExprKind::Literal(Lit::Int {
+                    value: 2,
+                    suffix: None
+                })
+                .into()
+            ),
+        })
         .to_string(),
         "(1 + 2)"
     );
-    assert_eq!(Expr::Literal(Lit::Byt("a".to_string())).to_string(), "`a`");
+    assert_eq!(
+        Expr::from(ExprKind::Literal(Lit::Byt(b'a'))).to_string(),
+        "`a`"
+    );
+    let path = Expr::from(ExprKind::Path {
+        segments: vec!["SOURCES".to_string(), "stdin".to_string()],
+    });
+    let call = Expr::from(ExprKind::PathCall {
+        path: Box::new(path),
+        args: vec![],
+    });
+    assert_eq!(call.to_string(), "SOURCES::stdin()");
+    assert_eq!(
+        Expr::from(ExprKind::MethodCall {
+            receiver: Box::new(call),
+            method: "get".to_string(),
+            args: vec![ExprKind::Ident("i".to_string()).into()],
+        })
+        .to_string(),
+        "SOURCES::stdin().get(i)"
+    );
+    assert_eq!(
+        Expr::from(ExprKind::Literal(Lit::Str(b"a\tb\n".to_vec()))).to_string(),
+        "\"a\\tb\\n\""
+    );
+    assert_eq!(
+        Expr::from(ExprKind::Literal(Lit::Byt(b'`'))).to_string(),
+        "`\\``"
+    );
+    assert_eq!(
+        Type::new(
+            "stream",
+            vec![Type::new("stream", vec![Type::new("u8", vec![])])]
+        )
+        .to_string(),
+        "stream<stream<u8>>"
+    );
+}
+
+#[test]
+fn spans_ignored_by_eq() {
+    assert_eq!(
+        Expr::new(ExprKind::Ident("foo".to_string()), 0..3),
+        Expr::new(ExprKind::Ident("foo".to_string()), 10..13),
+    );
+    assert_ne!(
+        Expr::new(ExprKind::Ident("foo".to_string()), 0..3),
+        Expr::new(ExprKind::Ident("bar".to_string()), 0..3),
+    );
 }
@@ -0,0 +1,78 @@
+use crate::{lexer::Token, span::Span};
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    // tokens that would have been accepted at this point
+    pub expected: Vec<Token>,
+    pub found: Token,
+    pub span: Span,
+    // replaces the generated "expected .., found .." text, for errors that
+    // cannot be described as a list of tokens
+    pub message: Option<String>,
+}
+
+impl ParseError {
+    pub fn new(expected: Vec<Token>, found: Token, span: Span) -> Self {
+        Self {
+            expected,
+            found,
+            span,
+            message: None,
+        }
+    }
+    pub fn with_message(message: String, found: Token, span: Span) -> Self {
+        Self {
+            expected: vec![],
+            found,
+            span,
+            message: Some(message),
+        }
+    }
+    // error for when a whole construct was expected, e.g. "expression"
+    pub fn expected(what: &str, found: Token, span: Span) -> Self {
+        Self::with_message(format!("expected {}, found `{}`", what, found), found, span)
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        if let Some(message) = &self.message {
+            return write!(f, "{}", message);
+        }
+        match self.expected.as_slice() {
+            [] => write!(f, "unexpected `{}`", self.found),
+            [tok] => write!(f, "expected `{}`, found `{}`", tok, self.found),
+            toks => {
+                write!(f, "expected one of ")?;
+                for (i, tok) in toks.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "`{}`", tok)?;
+                }
+                write!(f, ", found `{}`", self.found)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[test]
+fn display() {
+    use crate::T;
+
+    assert_eq!(
+        ParseError::new(vec![T![;]], T!['}'], 0..1).to_string(),
+        "expected `;`, found `}`"
+    );
+    assert_eq!(
+        ParseError::new(vec![T![:=], T![=]], T![ident], 0..1).to_string(),
+        "expected one of `:=`, `=`, found `ident`"
+    );
+    assert_eq!(
+        ParseError::expected("expression", T![;], 0..1).to_string(),
+        "expected expression, found `;`"
+    );
+}
@@ -1,94 +1,142 @@
 use super::ast;
-use super::Parser;
+use super::literals::{parse_int, unescape};
+use super::{ParseError, Parser};
 use crate::{lexer::Token, T};
-use ast::Expr;
+use ast::{Expr, ExprKind};
 
 impl<'input, I> Parser<'input, I>
 where
-    I: Iterator<Item = (Token, std::ops::Range<usize>)>,
+    I: Iterator<Item = (Token, crate::span::Span)>,
 {
-    pub fn expression(&mut self) -> Expr {
-        self.parse_expression(0)
+    pub fn expression(&mut self) -> Result<Expr, Vec<ParseError>> {
+        let expr = self.parse_expression(0);
+        self.check_recovered(expr)
     }
     // TODO:
     // * true recursive descent
     // * refactor common operations
     // * define context free grammar
-    fn parse_expression(&mut self, binding_power: u8) -> Expr {
+    pub(super) fn parse_expression(&mut self, binding_power: u8) -> Result<Expr, ParseError> {
+        let expr = self.expression_or_write(binding_power)?;
+        if let Some(stream) = write_target(&expr) {
+            return Err(ParseError::with_message(
+                "a write to a stream must be a statement of its own".to_string(),
+                T![->],
+                expr.span.start..stream.span.end,
+            ));
+        }
+        Ok(expr)
+    }
+    // like `parse_expression`, but also accepts `value -> stream`, which is
+    // returned as an `->` infix operator for the statement to take apart
+    pub(super) fn expression_or_write(&mut self, binding_power: u8) -> Result<Expr, ParseError> {
+        let start = self.start();
         let mut lhs = match self.peek() {
-            T![num(_)] | T![string] | T![byte] => self.literal(),
+            T![num] | T![string] | T![byte] | T![true] | T![false] | T![none] => self.literal()?,
             T![ident] => {
                 let (tok, ident_name) = self.next().unwrap();
                 if !self.at(T!['(']) {
-                    Expr::Ident(ident_name.to_string())
+                    Expr::new(
+                        ExprKind::Ident(ident_name.to_string()),
+                        self.span_from(start),
+                    )
                 } else {
                     self.push((tok, ident_name));
-                    self.fn_call()
+                    self.fn_call()?
                 }
             }
             T!['('] => {
-                let tup = self.tuple();
+                let tup = self.tuple()?;
                 // tuple with one expression is just the expression
                 // this is needed for arithmetic extension
-                if let Expr::Tuple(mut vec) = tup {
+                if let ExprKind::Tuple(mut vec) = tup.kind {
                     if vec.len() == 1 {
-                        vec.swap_remove(0)
+                        Expr::new(vec.swap_remove(0).kind, tup.span)
                     } else {
-                        Expr::Tuple(vec)
+                        Expr::new(ExprKind::Tuple(vec), tup.span)
                     }
                 } else {
                     unreachable!()
                 }
             }
-            op @ Token::Plus | op @ Token::Minus | op @ Token::Bang => {
-                self.consume(op);
+            op @ (T![+] | T![-] | T![!] | T![~]) => {
+                self.consume(op)?;
                 let (_, right_binding_power) = op.prefix_binding_power();
-                let expr = self.parse_expression(right_binding_power);
-                Expr::PrefixOp {
-                    op,
-                    expr: Box::new(expr),
-                }
+                let expr = self.parse_expression(right_binding_power)?;
+                Expr::new(
+                    ExprKind::PrefixOp {
+                        op,
+                        expr: Box::new(expr),
+                    },
+                    self.span_from(start),
+                )
             }
             T![_] => {
-                self.consume(T![_]);
-                Expr::Placeholder
+                self.consume(T![_])?;
+                Expr::new(ExprKind::Placeholder, self.span_from(start))
             }
-            kind => panic!("Unknown start of expression: `{}`", kind),
+            kind => return Err(ParseError::expected("expression", kind, self.peek_span())),
         };
 
         // extend lhs expression
-        loop {
-            let op = match self.peek() {
-                op @ T![+]
-                | op @ T![-]
-                | op @ T![*]
-                | op @ T![/]
-                | op @ T![^]
-                | op @ T![==]
-                | op @ T![!=]
-                | op @ T![&&]
-                | op @ T![||]
-                | op @ T![<]
-                | op @ T![<=]
-                | op @ T![>]
-                | op @ T![>=]
-                | op @ T![!]
-                | op @ T![.]
-                | op @ T![::]
-                | op @ T![->] => op,
-                T![eof] | T![')'] | T!['}'] | T!['{'] | T![,] | T![;] => break,
-                kind => panic!("Unknown operator: `{}`", kind),
-            };
-
+        // - any other token ends the expression; the caller decides whether
+        //   it is allowed to follow
+        // - a write to a stream is never extended
+        while let op @ (T![+]
+        | T![-]
+        | T![*]
+        | T![/]
+        | T![%]
+        | T![&]
+        | T![|]
+        | T![^]
+        | T![<<]
+        | T![>>]
+        | T![==]
+        | T![!=]
+        | T![&&]
+        | T![||]
+        | T![<]
+        | T![<=]
+        | T![>]
+        | T![>=]
+        | T![!]
+        | T![?]
+        | T!['[']
+        | T![.]
+        | T![::]
+        | T![->]) = self.peek()
+        {
+            if write_target(&lhs).is_some() {
+                break;
+            }
             if let Some((left_bp, _)) = op.postfix_binding_power() {
                 if left_bp < binding_power {
                     break;
                 }
-                self.consume(op);
-                lhs = Expr::PostfixOp {
-                    op,
-                    expr: Box::new(lhs),
-                };
+                if op == T![.] || op == T![::] {
+                    lhs = self.member(lhs, start)?;
+                    continue;
+                }
+                if op == T!['['] {
+                    let index = self.index()?;
+                    lhs = Expr::new(
+                        ExprKind::Index {
+                            expr: Box::new(lhs),
+                            index: Box::new(index),
+                        },
+                        self.span_from(start),
+                    );
+                    continue;
+                }
+                self.consume(op)?;
+                lhs = Expr::new(
+                    ExprKind::PostfixOp {
+                        op,
+                        expr: Box::new(lhs),
+                    },
+                    self.span_from(start),
+                );
                 continue;
             }
 
@@ -96,110 +144,233 @@ where
                 if left_bp < binding_power {
                     break;
                 }
-                self.consume(op);
-                let mut rhs = self.parse_expression(right_bp);
-                if op != T![->] || matches!(rhs, Expr::Ident(_)) {
-                    lhs = Expr::InfixOp {
-                        op,
-                        lhs: Box::new(lhs),
-                        rhs: Box::new(rhs),
-                    };
-                    continue;
-                }
-                if let Expr::FnCall {
-                    fn_name: _,
-                    ref mut args,
-                } = rhs
-                {
-                    let lhs_args = if let Expr::Tuple(items) = lhs {
-                        items
-                    } else {
-                        vec![lhs]
-                    };
-                    for arg in lhs_args {
-                        // try replacing the first Expr::Placeholder, otherwise append to args
-                        if let Some(index) = args.iter().position(|a| *a == Expr::Placeholder) {
-                            args[index] = arg;
-                        } else {
-                            args.push(arg);
+                self.consume(op)?;
+                let rhs_token = self.peek();
+                let rhs = self.parse_expression(right_bp)?;
+                let kind = match rhs.kind {
+                    // `value -> call`
+                    ExprKind::FnCall { .. }
+                    | ExprKind::PathCall { .. }
+                    | ExprKind::MethodCall { .. }
+                        if op == T![->] =>
+                    {
+                        ExprKind::Pipe {
+                            value: Box::new(lhs),
+                            call: Box::new(rhs),
                         }
                     }
-                    lhs = rhs;
-                } else {
-                    // could be a FnCall after a dot / double colon operator
-                    let mut expr = &mut rhs;
-                    loop {
-                        if let Expr::InfixOp { op, rhs, .. } = expr {
-                            if *op != T![.] && *op != T![::] {
-                                panic!(
-                                    "Expected a function call after the arrow operator, found `{}`",
-                                    rhs,
-                                );
+                    // `value -> stream` or `values -> (stream, ..)`
+                    ExprKind::Ident(_) | ExprKind::Tuple(_) if op == T![->] => {
+                        if let ExprKind::Tuple(streams) = &rhs.kind {
+                            if let Some(other) = streams.iter().find(|s| !is_stream(s)) {
+                                return Err(ParseError::with_message(
+                                    format!("expected a stream, found `{}`", other),
+                                    T!['('],
+                                    other.span.clone(),
+                                ));
                             }
-                            expr = rhs;
                         }
-                        if let Expr::FnCall {
-                            fn_name: _,
-                            ref mut args,
-                        } = expr
-                        {
-                            args.push(lhs);
-                            lhs = rhs;
-                            break;
+                        ExprKind::InfixOp {
+                            op,
+                            lhs: Box::new(lhs),
+                            rhs: Box::new(rhs),
                         }
                     }
-                }
+                    _ if op == T![->] => {
+                        return Err(ParseError::expected(
+                            "a function call, a stream or a tuple of streams after `->`",
+                            rhs_token,
+                            rhs.span,
+                        ))
+                    }
+                    _ => ExprKind::InfixOp {
+                        op,
+                        lhs: Box::new(lhs),
+                        rhs: Box::new(rhs),
+                    },
+                };
+                lhs = Expr::new(kind, self.span_from(start));
                 continue;
             }
             break;
         }
 
-        lhs
+        Ok(lhs)
     }
 
-    fn literal(&mut self) -> Expr {
+    fn literal(&mut self) -> Result<Expr, ParseError> {
+        let start = self.start();
         let (literal_token, literal_text) = self.next().unwrap();
         let lit = match literal_token {
-            T![num(n)] => ast::Lit::Int(n),
-            T![string] => ast::Lit::Str(literal_text[1..literal_text.len() - 1].to_string()),
-            T![byte] => ast::Lit::Byt(literal_text[1..literal_text.len() - 1].to_string()),
-            tok => panic!("Unexpected literal token: {:?}", tok),
+            T![num] => {
+                let (value, suffix) = parse_int(literal_text, start)?;
+                ast::Lit::Int { value, suffix }
+            }
+            T![string] | T![byte] => {
+                // invalid escapes are reported, but the literal is kept
+                let body = &literal_text[1..literal_text.len() - 1];
+                let (bytes, errors) = unescape(body, start + 1, literal_token);
+                self.errors.extend(errors);
+                if literal_token == T![string] {
+                    ast::Lit::Str(bytes)
+                } else if bytes.len() == 1 {
+                    ast::Lit::Byt(bytes[0])
+                } else {
+                    return Err(ParseError::with_message(
+                        format!(
+                            "byte literal must contain exactly one byte, found {}",
+                            bytes.len()
+                        ),
+                        literal_token,
+                        self.span_from(start),
+                    ));
+                }
+            }
+            T![true] => ast::Lit::Bool(true),
+            T![false] => ast::Lit::Bool(false),
+            T![none] => ast::Lit::None,
+            tok => unreachable!("Unexpected literal token: {:?}", tok),
         };
-        Expr::Literal(lit)
+        Ok(Expr::new(ExprKind::Literal(lit), self.span_from(start)))
     }
-    fn fn_call(&mut self) -> Expr {
-        let (_, ident_name) = self.next().unwrap();
-        // function call
-        let mut args = Vec::new();
-        self.consume(T!['(']);
-        while !self.at(T![')']) {
-            let arg = self.parse_expression(0);
-            args.push(arg);
-            if self.at(T![,]) {
-                self.consume(T![,]);
-            } else if !self.at(T![')']) {
-                panic!("Unexpected token");
+    // parse what follows `.` or `::` after `lhs`
+    // .ident(args) or ::ident or ::ident(args)
+    fn member(&mut self, lhs: Expr, start: usize) -> Result<Expr, ParseError> {
+        let op = self.peek();
+        let receiver = match (op, lhs.kind) {
+            (T![.], kind) => Expr::new(kind, lhs.span),
+            (T![::], ExprKind::Ident(name)) => Expr::new(
+                ExprKind::Path {
+                    segments: vec![name],
+                },
+                lhs.span,
+            ),
+            (T![::], kind @ ExprKind::Path { .. }) => Expr::new(kind, lhs.span),
+            (_, _) => {
+                return Err(ParseError::with_message(
+                    "expected a namespace before `::`".to_string(),
+                    op,
+                    lhs.span,
+                ))
             }
+        };
+        self.consume(op)?;
+        let name = self.ident()?.to_string();
+        if op == T![::] {
+            let mut segments = match receiver.kind {
+                ExprKind::Path { segments } => segments,
+                _ => unreachable!(),
+            };
+            segments.push(name);
+            let path = Expr::new(ExprKind::Path { segments }, self.span_from(start));
+            if !self.at(T!['(']) {
+                return Ok(path);
+            }
+            let args = self.expression_list()?;
+            return Ok(Expr::new(
+                ExprKind::PathCall {
+                    path: Box::new(path),
+                    args,
+                },
+                self.span_from(start),
+            ));
         }
-        self.consume(T![')']);
-        Expr::FnCall {
-            fn_name: ident_name.to_string(),
-            args,
-        }
+        let args = self.expression_list()?;
+        Ok(Expr::new(
+            ExprKind::MethodCall {
+                receiver: Box::new(receiver),
+                method: name,
+                args,
+            },
+            self.span_from(start),
+        ))
     }
-    fn tuple(&mut self) -> Expr {
-        self.consume(T!['(']);
+    // parse the index in `expr[index]`, which may be a range
+    // [expr] or [expr? .. expr?]
+    fn index(&mut self) -> Result<Expr, ParseError> {
+        self.consume(T!['['])?;
+        let start = self.start();
+        let first = if self.at(T![..]) {
+            None
+        } else {
+            Some(self.parse_expression(0)?)
+        };
+        let index = match first {
+            Some(index) if !self.at(T![..]) => index,
+            first => {
+                self.consume(T![..])?;
+                let end = if self.at(T![']']) {
+                    None
+                } else {
+                    Some(Box::new(self.parse_expression(0)?))
+                };
+                Expr::new(
+                    ExprKind::Range {
+                        start: first.map(Box::new),
+                        end,
+                    },
+                    self.span_from(start),
+                )
+            }
+        };
+        self.consume(T![']'])?;
+        Ok(index)
+    }
+    fn fn_call(&mut self) -> Result<Expr, ParseError> {
+        let start = self.start();
+        let ident_name = self.ident()?;
+        // function call
+        let args = self.expression_list()?;
+        Ok(Expr::new(
+            ExprKind::FnCall {
+                fn_name: ident_name.to_string(),
+                args,
+            },
+            self.span_from(start),
+        ))
+    }
+    fn tuple(&mut self) -> Result<Expr, ParseError> {
+        let start = self.start();
+        let args = self.expression_list()?;
+        Ok(Expr::new(ExprKind::Tuple(args), self.span_from(start)))
+    }
+    // parse comma separated expressions surrounded by parentheses
+    pub(super) fn expression_list(&mut self) -> Result<Vec<Expr>, ParseError> {
+        self.consume(T!['('])?;
         let mut args = vec![];
         while !self.at(T![')']) {
-            args.push(self.parse_expression(0));
+            args.push(self.parse_expression(0)?);
             if self.at(T![,]) {
-                self.consume(T![,]);
+                self.consume(T![,])?;
             } else if !self.at(T![')']) {
-                panic!("Unexpected token");
+                return Err(self.unexpected(vec![T![,], T![')']]));
             }
         }
-        self.consume(T![')']);
-        Expr::Tuple(args)
+        self.consume(T![')'])?;
+        Ok(args)
+    }
+}
+
+// streams written to are variables or calls giving a stream, such as
+// `SINKS::stdout()`
+fn is_stream(expr: &Expr) -> bool {
+    matches!(
+        expr.kind,
+        ExprKind::Ident(_)
+            | ExprKind::FnCall { .. }
+            | ExprKind::PathCall { .. }
+            | ExprKind::MethodCall { .. }
+    )
+}
+
+// the stream, or tuple of streams, written to when `expr` is
+// `value -> stream`
+pub(super) fn write_target(expr: &Expr) -> Option<&Expr> {
+    match &expr.kind {
+        ExprKind::InfixOp {
+            op: T![->], rhs, ..
+        } => Some(rhs),
+        _ => None,
     }
 }
 
@@ -212,7 +383,7 @@ trait Operator {
 impl Operator for Token {
     fn prefix_binding_power(&self) -> ((), u8) {
         match self {
-            T![+] | T![-] | T![!] => ((), 51),
+            T![+] | T![-] | T![!] | T![~] => ((), 51),
             _ => unreachable!("Not a prefix operator: `{:?}`", self),
         }
     }
@@ -220,12 +391,15 @@ impl Operator for Token {
         let result = match self {
             T![||] => (1, 2),
             T![&&] => (3, 4),
-            T![==] | T![!=] => (5, 6),
-            T![<] | T![>] | T![<=] | T![>=] => (7, 8),
-            T![->] => (9, 10),
-            T![+] | T![-] => (11, 12),
-            T![*] | T![/] => (13, 14),
-            T![.] | T![::] => (24, 23),
+            T![|] => (5, 6),
+            T![^] => (7, 8),
+            T![&] => (9, 10),
+            T![==] | T![!=] => (11, 12),
+            T![<] | T![>] | T![<=] | T![>=] => (13, 14),
+            T![->] => (15, 16),
+            T![<<] | T![>>] => (17, 18),
+            T![+] | T![-] => (19, 20),
+            T![*] | T![/] | T![%] => (21, 22),
             _ => return None,
         };
         Some(result)
@@ -233,6 +407,13 @@ impl Operator for Token {
     fn postfix_binding_power(&self) -> Option<(u8, ())> {
         let result = match self {
             T![!] => (101, ()),
+            // binds tighter than prefix operators, so `!s.eof()` negates
+            // the call
+            T![.] | T![::] => (62, ()),
+            // between prefix operators and member access, so `!s[0]` and
+            // `s.next()[0]` index before anything else applies, and
+            // `s.next()?` unwraps the result of the call
+            T!['['] | T![?] => (60, ()),
             _ => return None,
         };
         Some(result)
@@ -1,191 +1,304 @@
 use super::ast;
-use super::Parser;
+use super::{ParseError, Parser};
 use crate::{lexer::Token, T};
 
 impl<'input, I> Parser<'input, I>
 where
-    I: Iterator<Item = (Token, std::ops::Range<usize>)>,
+    I: Iterator<Item = (Token, crate::span::Span)>,
 {
-    pub fn statement(&mut self) -> ast::Stmt {
-        match self.peek() {
-            T![ident] => {
-                let (_, ident) = self.next().unwrap();
+    pub fn statement(&mut self) -> Result<ast::Stmt, Vec<ParseError>> {
+        let stmt = self.parse_statement();
+        self.check_recovered(stmt)
+    }
+
+    fn parse_statement(&mut self) -> Result<ast::Stmt, ParseError> {
+        let start = self.start();
+        let kind = match self.peek() {
+            // `_` discards a value it is given
+            tok @ (T![ident] | T![_]) => {
+                let (_, text) = self.next().unwrap();
+                let ident = self.name(text);
                 match self.peek() {
                     op @ T![:=] => {
-                        self.consume(op);
-                        let value = self.expression();
-                        self.consume(T![;]);
-                        ast::Stmt::Declaration {
-                            var_names: vec![ident.to_string()],
+                        self.consume(op)?;
+                        let value = self.parse_expression(0)?;
+                        self.end_of_statement()?;
+                        ast::StmtKind::Declaration {
+                            var_names: vec![ident],
                             value,
                         }
                     }
                     op @ T![=] => {
-                        self.consume(op);
-                        let value = self.expression();
-                        self.consume(T![;]);
-                        ast::Stmt::Assignment {
-                            var_names: vec![ident.to_string()],
+                        self.consume(op)?;
+                        let value = self.parse_expression(0)?;
+                        self.end_of_statement()?;
+                        ast::StmtKind::Assignment {
+                            var_names: vec![ident],
+                            value,
+                        }
+                    }
+                    op @ (T![+=]
+                    | T![-=]
+                    | T![*=]
+                    | T![/=]
+                    | T![%=]
+                    | T![&=]
+                    | T![|=]
+                    | T![^=]
+                    | T![<<=]
+                    | T![>>=])
+                        if tok == T![ident] =>
+                    {
+                        self.consume(op)?;
+                        let value = self.parse_expression(0)?;
+                        self.end_of_statement()?;
+                        ast::StmtKind::CompoundAssignment {
+                            var_name: ident,
+                            op: binary_operator(op),
                             value,
                         }
                     }
+                    op @ (T![++] | T![--]) if tok == T![ident] => {
+                        self.consume(op)?;
+                        self.end_of_statement()?;
+                        let var_name = ident;
+                        if op == T![++] {
+                            ast::StmtKind::Increment { var_name }
+                        } else {
+                            ast::StmtKind::Decrement { var_name }
+                        }
+                    }
                     T![,] => {
                         // destructured declaration or assignment
                         // 1. loop and collect all identifiers into a vector
-                        let mut idents = vec![ident.to_string()];
+                        let mut idents = vec![ident];
                         while self.peek() == T![,] {
-                            self.consume(T![,]);
-                            let (tok_type, tok_name) = self
-                                .next()
-                                .expect("Expected another token but there were none");
-                            assert_eq!(
-                                tok_type,
-                                Token::Ident,
-                                "Expected an identifier, but found {}",
-                                tok_type
-                            );
-                            idents.push(tok_name.to_string());
+                            self.consume(T![,])?;
+                            idents.push(self.target()?);
                         }
                         // 2. consume declaration / assignment operator
                         // 3. create statement
                         // 4. consume semicolon
                         match self.peek() {
                             op @ T![:=] => {
-                                self.consume(op);
-                                let value = self.expression();
-                                self.consume(T![;]);
-                                ast::Stmt::Declaration {
+                                self.consume(op)?;
+                                let value = self.parse_expression(0)?;
+                                self.end_of_statement()?;
+                                ast::StmtKind::Declaration {
                                     var_names: idents,
                                     value,
                                 }
                             }
                             op @ T![=] => {
-                                self.consume(op);
-                                let value = self.expression();
-                                self.consume(T![;]);
-                                ast::Stmt::Assignment {
+                                self.consume(op)?;
+                                let value = self.parse_expression(0)?;
+                                self.end_of_statement()?;
+                                ast::StmtKind::Assignment {
                                     var_names: idents,
                                     value,
                                 }
                             }
-                            op => panic!("Unexpected operator {}", op),
+                            _ => return Err(self.unexpected(vec![T![,], T![:=], T![=]])),
                         }
                     }
                     _ => {
-                        self.push((T![ident], ident));
-                        let expr = self.expression();
-                        self.consume(T![;]);
-                        ast::Stmt::Expr(expr)
+                        self.push((tok, text));
+                        self.expression_statement()?
                     }
                 }
             }
             T![if] => {
-                self.consume(T![if]);
-                let condition = self.expression();
-                assert!(self.at(T!['{']), "Expected a block after `if` statement");
-                let body = match self.statement() {
-                    ast::Stmt::Block { stmts } => stmts,
-                    _ => unreachable!(),
-                };
+                self.consume(T![if])?;
+                let condition = self.parse_expression(0)?;
+                let body = self.block()?;
 
                 let else_stmt = if self.at(T![else]) {
-                    self.consume(T![else]);
-                    assert!(
-                        self.at(T![if]) || self.at(T!['{']),
-                        "Expected a block or an `if` after `else` statement"
-                    );
-                    Some(Box::new(self.statement()))
+                    self.consume(T![else])?;
+                    if !self.at(T![if]) && !self.at(T!['{']) {
+                        return Err(self.unexpected(vec![T![if], T!['{']]));
+                    }
+                    Some(Box::new(self.parse_statement()?))
                 } else {
                     None
                 };
-                ast::Stmt::IfStmt {
-                    condition: condition,
+                ast::StmtKind::IfStmt {
+                    condition,
                     body,
                     else_stmt,
                 }
             }
             T!['{'] => {
-                self.consume(T!['{']);
-                let mut stmts = Vec::new();
-                while !self.at(T!['}']) {
-                    stmts.push(self.statement());
-                }
-                self.consume(T!['}']);
-                ast::Stmt::Block { stmts }
+                let stmts = self.block()?;
+                ast::StmtKind::Block { stmts }
             }
             T![return] => {
-                self.consume(T![return]);
-                if self.at(T![;]) {
-                    self.consume(T![;]);
-                    ast::Stmt::ReturnStmt { value: None }
-                } else {
-                    let expr = self.expression();
-                    self.consume(T![;]);
-                    ast::Stmt::ReturnStmt { value: Some(expr) }
+                self.consume(T![return])?;
+                let mut values = Vec::new();
+                if !self.at(T![;]) {
+                    values.push(self.parse_expression(0)?);
+                    while self.at(T![,]) {
+                        self.consume(T![,])?;
+                        values.push(self.parse_expression(0)?);
+                    }
                 }
+                self.end_of_statement()?;
+                ast::StmtKind::ReturnStmt { values }
             }
             T![continue] => {
-                self.consume(T![continue]);
-                self.consume(T![;]);
-                ast::Stmt::ContinueStmt
+                self.consume(T![continue])?;
+                self.end_of_statement()?;
+                ast::StmtKind::ContinueStmt
             }
-            T![for] => {
-                self.consume(T![for]);
-                let (ident_tok, ident_name) = self
-                    .next()
-                    .expect("Tried to parse identifier, but there were no more tokens");
-                assert_eq!(
-                    ident_tok,
-                    T![ident],
-                    "Expected identifier at start of type, but found `{}`",
-                    ident_tok
-                );
-                self.consume(T![in]);
-                let stream = self.expression();
-                assert!(self.at(T!['{']), "Expected block after for header");
-                let body = match self.statement() {
-                    ast::Stmt::Block { stmts } => stmts,
-                    _ => unreachable!(),
+            T![break] => {
+                self.consume(T![break])?;
+                self.end_of_statement()?;
+                ast::StmtKind::BreakStmt
+            }
+            T![while] => {
+                self.consume(T![while])?;
+                let condition = self.parse_expression(0)?;
+                let stmts = self.block()?;
+                ast::StmtKind::WhileLoop { condition, stmts }
+            }
+            T![assert] => {
+                self.consume(T![assert])?;
+                let condition = self.parse_expression(0)?;
+                let message = if self.at(T![,]) {
+                    self.consume(T![,])?;
+                    Some(self.parse_expression(0)?)
+                } else {
+                    None
                 };
-                ast::Stmt::ForLoop {
-                    var_name: ident_name.to_string(),
-                    stream: stream,
+                self.end_of_statement()?;
+                ast::StmtKind::Assert { condition, message }
+            }
+            T![for] => {
+                self.consume(T![for])?;
+                let ident_name = self.ident()?;
+                let var_name = self.name(ident_name);
+                self.consume(T![in])?;
+                let stream = self.parse_expression(0)?;
+                let body = self.block()?;
+                ast::StmtKind::ForLoop {
+                    var_name,
+                    stream,
                     stmts: body,
                 }
             }
-            _ => {
-                let expr = self.expression();
-                self.consume(T![;]);
-                ast::Stmt::Expr(expr)
+            _ => self.expression_statement()?,
+        };
+        Ok(ast::Stmt::new(kind, self.span_from(start)))
+    }
+
+    // an expression on its own, or `value -> stream;`
+    fn expression_statement(&mut self) -> Result<ast::StmtKind, ParseError> {
+        let expr = self.expression_or_write(0)?;
+        self.end_of_statement()?;
+        match expr.kind {
+            ast::ExprKind::InfixOp {
+                op: T![->],
+                lhs,
+                rhs,
+            } => Ok(ast::StmtKind::StreamWrite {
+                value: *lhs,
+                stream: *rhs,
+            }),
+            kind => Ok(ast::StmtKind::Expr(ast::Expr::new(kind, expr.span))),
+        }
+    }
+
+    // parse statements surrounded by curly brackets
+    // - a statement that fails to parse is recorded as an error and
+    //   replaced by an error node, then parsing continues after it
+    fn block(&mut self) -> Result<Vec<ast::Stmt>, ParseError> {
+        self.consume(T!['{'])?;
+        let mut stmts = Vec::new();
+        while !self.at(T!['}']) && !self.at(T![eof]) && !self.at(T![fn]) && !self.at(T![#]) {
+            let start = self.start();
+            match self.parse_statement() {
+                Ok(stmt) => stmts.push(stmt),
+                Err(err) => {
+                    self.errors.push(err);
+                    self.synchronize();
+                    stmts.push(ast::Stmt::new(ast::StmtKind::Error, self.span_from(start)));
+                }
+            }
+        }
+        self.consume(T!['}'])?;
+        Ok(stmts)
+    }
+
+    // statements end with a semicolon; when it is missing before a `}` or
+    // at the end of a line, report it and carry on as if it were there
+    fn end_of_statement(&mut self) -> Result<(), ParseError> {
+        if self.at(T![;]) {
+            return self.consume(T![;]);
+        }
+        let err = self.unexpected(vec![T![;]]);
+        let gap = &self.input[self.last_span.end.min(err.span.start)..err.span.start];
+        if self.at(T!['}']) || gap.contains('\n') {
+            self.errors.push(err);
+            return Ok(());
+        }
+        Err(err)
+    }
+
+    // skip to the end of the statement that failed to parse: past the next
+    // `;`, past a block that was opened, or up to the `}` closing the
+    // enclosing block or the next item
+    fn synchronize(&mut self) {
+        let mut depth = 0;
+        loop {
+            match self.peek() {
+                T![eof] | T![fn] | T![#] => return,
+                T!['}'] if depth == 0 => return,
+                T!['}'] => {
+                    self.next();
+                    depth -= 1;
+                    if depth == 0 {
+                        return;
+                    }
+                }
+                T!['{'] => {
+                    self.next();
+                    depth += 1;
+                }
+                T![;] if depth == 0 => {
+                    self.next();
+                    return;
+                }
+                _ => {
+                    self.next();
+                }
             }
         }
     }
 
-    pub fn type_(&mut self) -> ast::Type {
-        let (ident, name) = self
-            .next()
-            .expect("Tried to parse type, but there were no more tokens");
-        assert_eq!(
-            ident,
-            T![ident],
-            "Expected identifier at start of type, but found `{}`",
-            ident
-        );
+    pub fn type_(&mut self) -> Result<ast::Type, ParseError> {
+        let start = self.start();
+        let name = self.ident()?;
         let mut generics = Vec::new();
         if self.at(T![<]) {
-            self.consume(T![<]);
-            while !self.at(T![>]) {
-                generics.push(self.type_());
+            self.consume(T![<])?;
+            while !self.at(T![>]) && !self.at(T![>>]) {
+                generics.push(self.type_()?);
                 if self.at(T![,]) {
-                    self.consume(T![,]);
+                    self.consume(T![,])?;
                 }
             }
-            self.consume(T![>]);
+            // the first half of `>>` closes this list, the second half the
+            // enclosing one
+            if self.at(T![>>]) {
+                self.split_next(T![>]);
+            } else {
+                self.consume(T![>])?;
+            }
         }
-        ast::Type {
+        Ok(ast::Type {
             name: name.to_string(),
             generics,
-        }
+            span: self.span_from(start),
+        })
     }
 
     // parse function definitions
@@ -193,79 +306,140 @@ where
     // fn fn_name(ident ident_type*) -> (ident ident_type) { block }
     // or
     // fn fn_name(ident ident_type*) { block }
-    fn fn_definition(&mut self) -> ast::Item {
-        self.consume(T![fn]);
-        let (ident, name) = self
-            .next()
-            .expect("Tried to parse function name, but there were no more tokens");
-        assert_eq!(
-            ident,
-            T![ident],
-            "Expected identifier as function name, but found `{}`",
-            ident
-        );
-        let parameters = self.named_params();
-        assert!(
-            self.at(T!['{']) || self.at(T![->]),
-            "Expected block or return type after function header"
-        );
+    fn fn_definition(&mut self) -> Result<ast::Item, ParseError> {
+        let start = self.start();
+        self.consume(T![fn])?;
+        let name = self.ident()?;
+        let parameters = self.named_params()?;
+        // the `->` in front of the outputs may be left out
         let mut return_params = Vec::new();
         if self.at(T![->]) {
-            self.consume(T![->]);
-            return_params = self.named_params();
-        }
-        assert!(self.at(T!['{']), "Expected block after function header");
-        let body = match self.statement() {
-            ast::Stmt::Block { stmts } => stmts,
-            _ => unreachable!(),
-        };
-        ast::Item::Function {
-            name: name.to_string(),
-            parameters,
-            body,
-            return_params,
+            self.consume(T![->])?;
+            return_params = self.named_params()?;
+        } else if self.at(T!['(']) {
+            return_params = self.named_params()?;
+        } else if !self.at(T!['{']) {
+            return Err(self.unexpected(vec![T![->], T!['('], T!['{']]));
         }
+        let body = self.block()?;
+        Ok(ast::Item::new(
+            ast::ItemKind::Function {
+                name: name.to_string(),
+                parameters,
+                body,
+                return_params,
+            },
+            self.span_from(start),
+        ))
     }
 
     // parse named parameters (0 or more surrounded by parentheses)
     // (foo u8, bar u8)
-    fn named_params(&mut self) -> Vec<(String, ast::Type)> {
-        self.consume(T!['(']);
+    fn named_params(&mut self) -> Result<Vec<ast::Param>, ParseError> {
+        self.consume(T!['('])?;
         let mut parameters = Vec::new();
         while !self.at(T![')']) {
-            let (param, param_name) = self
-                .next()
-                .expect("Tried to parse parameter, but there were no more tokens");
-            assert_eq!(
-                param,
-                T![ident],
-                "Expected identifier as parameter, but found `{}`",
-                param
-            );
-            let param_type = self.type_();
-            parameters.push((param_name.to_string(), param_type));
+            let param_name = self.ident()?;
+            let span = self.last_span.clone();
+            let param_type = self.type_()?;
+            parameters.push(ast::Param {
+                name: param_name.to_string(),
+                typ: param_type,
+                span,
+            });
             if self.at(T![,]) {
-                self.consume(T![,]);
+                self.consume(T![,])?;
             }
         }
-        self.consume(T![')']);
-        parameters
+        self.consume(T![')'])?;
+        Ok(parameters)
     }
 
     // parse top level items
-    pub fn item(&mut self) -> ast::Item {
-        match self.peek() {
-            T![fn] => self.fn_definition(),
-            _ => unreachable!(),
+    pub fn item(&mut self) -> Result<ast::Item, Vec<ParseError>> {
+        let item = self.parse_item();
+        self.check_recovered(item)
+    }
+
+    fn parse_item(&mut self) -> Result<ast::Item, ParseError> {
+        let attributes = self.attributes()?;
+        let mut item = match self.peek() {
+            T![fn] => self.fn_definition()?,
+            _ => return Err(self.unexpected(vec![T![fn]])),
+        };
+        item.attributes = attributes;
+        Ok(item)
+    }
+
+    // parse the attributes in front of an item
+    // #[name] or #[name(expr*)]
+    fn attributes(&mut self) -> Result<Vec<ast::Attribute>, ParseError> {
+        let mut attributes = Vec::new();
+        while self.at(T![#]) {
+            let start = self.start();
+            self.consume(T![#])?;
+            self.consume(T!['['])?;
+            let name = self.ident()?;
+            let args = if self.at(T!['(']) {
+                self.expression_list()?
+            } else {
+                Vec::new()
+            };
+            self.consume(T![']'])?;
+            attributes.push(ast::Attribute {
+                name: name.to_string(),
+                args,
+                span: self.span_from(start),
+            });
+        }
+        Ok(attributes)
+    }
+
+    pub fn file(&mut self) -> Result<Vec<ast::Item>, Vec<ParseError>> {
+        let (items, errors) = self.file_with_errors();
+        if errors.is_empty() {
+            Ok(items)
+        } else {
+            Err(errors)
         }
     }
 
-    pub fn file(&mut self) -> Vec<ast::Item> {
+    // parse the whole input, recovering from syntax errors
+    // - returns every error along with the partial AST, where the parts
+    //   that failed to parse are replaced by error nodes
+    pub fn file_with_errors(&mut self) -> (Vec<ast::Item>, Vec<ParseError>) {
         let mut items = Vec::new();
         while !self.at(T![eof]) {
-            let item = self.item();
-            items.push(item);
+            let start = self.start();
+            match self.parse_item() {
+                Ok(item) => items.push(item),
+                Err(err) => {
+                    self.errors.push(err);
+                    // skip to the next item
+                    while !self.at(T![fn]) && !self.at(T![#]) && !self.at(T![eof]) {
+                        self.next();
+                    }
+                    items.push(ast::Item::new(ast::ItemKind::Error, self.span_from(start)));
+                }
+            }
         }
-        items
+        (items, std::mem::take(&mut self.errors))
+    }
+}
+
+// the binary operator applied by a compound assignment operator
+fn binary_operator(op: Token) -> Token {
+    match op {
+        T![+=] => T![+],
+        T![-=] => T![-],
+        T![*=] => T![*],
+        T![/=] => T![/],
+        T![%=] => T![%],
+        T![&=] => T![&],
+        T![|=] => T![|],
+        T![^=] => T![^],
+        T![<<=] => T![<<],
+        T![>>=] => T![>>],
+        _ => unreachable!("Not a compound assignment operator: `{:?}`", op),
     }
 }
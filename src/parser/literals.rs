@@ -0,0 +1,228 @@
+use super::ast::IntSuffix;
+use super::ParseError;
+use crate::{lexer::Token, span::Span, T};
+
+// decodes the escape sequences in the body of a string or byte literal
+// - `start` is the offset of `body` in the input, used for error spans
+// - invalid escape sequences are reported and skipped
+pub fn unescape(body: &str, start: usize, token: Token) -> (Vec<u8>, Vec<ParseError>) {
+    let mut bytes = Vec::with_capacity(body.len());
+    let mut errors = Vec::new();
+    let mut chars = body.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        let escape = match chars.next() {
+            Some((_, escape)) => escape,
+            // the lexer never ends a literal on a backslash
+            None => unreachable!("Literal ends with a backslash"),
+        };
+        let byte = match escape {
+            'n' => b'\n',
+            'r' => b'\r',
+            't' => b'\t',
+            '0' => b'\0',
+            '\\' => b'\\',
+            '\'' => b'\'',
+            '"' => b'"',
+            '`' => b'`',
+            'x' => {
+                let digits: String = (0..2)
+                    .filter_map(|_| chars.next_if(|(_, c)| c.is_ascii_hexdigit()))
+                    .map(|(_, c)| c)
+                    .collect();
+                if digits.len() != 2 {
+                    let end = start + i + 2 + digits.len();
+                    errors.push(ParseError::with_message(
+                        "expected two hex digits after `\\x`".to_string(),
+                        token,
+                        start + i..end,
+                    ));
+                    continue;
+                }
+                u8::from_str_radix(&digits, 16).unwrap()
+            }
+            other => {
+                let span: Span = start + i..start + i + 1 + other.len_utf8();
+                errors.push(ParseError::with_message(
+                    format!("unknown escape sequence `\\{}`", other),
+                    token,
+                    span,
+                ));
+                continue;
+            }
+        };
+        bytes.push(byte);
+    }
+    (bytes, errors)
+}
+
+// parses an integer literal such as `1_000`, `0xFF`, `0o17`, `0b1010` or
+// `255u8` into its value and optional type suffix
+// - `start` is the offset of `text` in the input, used for error spans
+// - whether the value fits the suffix is left to the checker, which knows if
+//   the literal is negated
+pub fn parse_int(text: &str, start: usize) -> Result<(u64, Option<IntSuffix>), ParseError> {
+    let error = |message: String, span: Span| {
+        ParseError::with_message(message, T![num], start + span.start..start + span.end)
+    };
+    let (radix, base) = match text.get(..2) {
+        Some("0x") => (16, "hexadecimal"),
+        Some("0o") => (8, "octal"),
+        Some("0b") => (2, "binary"),
+        _ => (10, "decimal"),
+    };
+    let prefix_len = if radix == 10 { 0 } else { 2 };
+    // the suffix starts at the first `u` or `i`, which are never digits
+    let suffix_start = text[prefix_len..]
+        .find(['u', 'i'])
+        .map(|i| prefix_len + i)
+        .unwrap_or(text.len());
+    let suffix = match &text[suffix_start..] {
+        "" => None,
+        name => Some(IntSuffix::from_name(name).ok_or_else(|| {
+            error(
+                format!("invalid suffix `{}` for number literal", name),
+                suffix_start..text.len(),
+            )
+        })?),
+    };
+
+    let mut value: u64 = 0;
+    let mut has_digits = false;
+    for (i, c) in text[prefix_len..suffix_start].char_indices() {
+        if c == '_' {
+            continue;
+        }
+        let offset = prefix_len + i;
+        let digit = c.to_digit(radix).ok_or_else(|| {
+            error(
+                format!("invalid digit `{}` in {} literal", c, base),
+                offset..offset + c.len_utf8(),
+            )
+        })?;
+        has_digits = true;
+        value = value
+            .checked_mul(radix as u64)
+            .and_then(|value| value.checked_add(digit as u64))
+            .ok_or_else(|| {
+                let message = match suffix {
+                    Some(suffix) => format!(
+                        "literal out of range for `{}`, the range is `{}`",
+                        suffix.name(),
+                        suffix.range()
+                    ),
+                    None => format!("integer literal is too large, the maximum is {}", u64::MAX),
+                };
+                error(message, 0..text.len())
+            })?;
+    }
+    if !has_digits {
+        return Err(error(
+            format!("missing digits after `{}`", &text[..prefix_len]),
+            0..text.len(),
+        ));
+    }
+    Ok((value, suffix))
+}
+
+// the inverse of `unescape`, used when printing literals
+// - `quote` is the delimiter of the literal, which needs to be escaped
+pub fn escape(bytes: &[u8], quote: u8) -> String {
+    let mut out = String::with_capacity(bytes.len());
+    for &byte in bytes {
+        match byte {
+            b'\n' => out.push_str("\\n"),
+            b'\r' => out.push_str("\\r"),
+            b'\t' => out.push_str("\\t"),
+            b'\0' => out.push_str("\\0"),
+            b'\\' => out.push_str("\\\\"),
+            b if b == quote => {
+                out.push('\\');
+                out.push(b as char);
+            }
+            b' '..=b'~' => out.push(byte as char),
+            _ => out.push_str(&format!("\\x{:02x}", byte)),
+        }
+    }
+    out
+}
+
+#[test]
+fn unescapes() {
+    use crate::T;
+
+    let decode = |body: &str| unescape(body, 0, T![string]);
+    assert_eq!(decode("foo"), (b"foo".to_vec(), vec![]));
+    assert_eq!(
+        decode(r#"a\n\r\t\0\\\'\"\`b"#),
+        (b"a\n\r\t\0\\'\"`b".to_vec(), vec![])
+    );
+    assert_eq!(decode(r"\x41\xfF\x00"), (vec![0x41, 0xff, 0x00], vec![]));
+    assert_eq!(decode("é"), ("é".as_bytes().to_vec(), vec![]));
+
+    let (bytes, errors) = decode(r"a\qb\x4");
+    assert_eq!(bytes, b"ab".to_vec());
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].to_string(), "unknown escape sequence `\\q`");
+    assert_eq!(errors[0].span, 1..3);
+    assert_eq!(errors[1].to_string(), "expected two hex digits after `\\x`");
+    assert_eq!(errors[1].span, 4..7);
+}
+
+#[test]
+fn parses_ints() {
+    assert_eq!(parse_int("0", 0), Ok((0, None)));
+    assert_eq!(parse_int("1_000", 0), Ok((1000, None)));
+    assert_eq!(parse_int("0xFF", 0), Ok((255, None)));
+    assert_eq!(parse_int("0xdead_BEEF", 0), Ok((0xdead_beef, None)));
+    assert_eq!(parse_int("0o17", 0), Ok((15, None)));
+    assert_eq!(parse_int("0b1010", 0), Ok((10, None)));
+    assert_eq!(parse_int("255u8", 0), Ok((255, Some(IntSuffix::U8))));
+    assert_eq!(parse_int("0xFFi64", 0), Ok((255, Some(IntSuffix::I64))));
+    assert_eq!(parse_int("0b1_u16", 0), Ok((1, Some(IntSuffix::U16))));
+    assert_eq!(
+        parse_int("18446744073709551615u64", 0),
+        Ok((u64::MAX, Some(IntSuffix::U64)))
+    );
+
+    let err = parse_int("18446744073709551616", 10).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "integer literal is too large, the maximum is 18446744073709551615"
+    );
+    assert_eq!(err.span, 10..30);
+    let err = parse_int("99999999999999999999999u8", 0).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "literal out of range for `u8`, the range is `0..=255`"
+    );
+    let err = parse_int("99999999999999999999999i16", 0).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "literal out of range for `i16`, the range is `-32768..=32767`"
+    );
+    let err = parse_int("0o19", 0).unwrap_err();
+    assert_eq!(err.to_string(), "invalid digit `9` in octal literal");
+    assert_eq!(err.span, 3..4);
+    let err = parse_int("0b102", 0).unwrap_err();
+    assert_eq!(err.to_string(), "invalid digit `2` in binary literal");
+    let err = parse_int("12ab", 0).unwrap_err();
+    assert_eq!(err.to_string(), "invalid digit `a` in decimal literal");
+    let err = parse_int("1u7", 0).unwrap_err();
+    assert_eq!(err.to_string(), "invalid suffix `u7` for number literal");
+    assert_eq!(err.span, 1..3);
+    let err = parse_int("0x_u8", 0).unwrap_err();
+    assert_eq!(err.to_string(), "missing digits after `0x`");
+}
+
+#[test]
+fn escapes() {
+    assert_eq!(escape(b"foo bar", b'"'), "foo bar");
+    assert_eq!(escape(b"a\n\"'`\\", b'"'), "a\\n\\\"'`\\\\");
+    assert_eq!(escape(b"`", b'`'), "\\`");
+    assert_eq!(escape(&[0x7f, 0xff], b'"'), "\\x7f\\xff");
+}
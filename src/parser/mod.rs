@@ -1,27 +1,36 @@
 pub mod ast;
+mod error;
 mod expressions;
 mod hierarchy;
+mod literals;
 
-use crate::{lexer::Token, T};
+pub use error::ParseError;
+
+use crate::{lexer::Token, span::Span, T};
 use logos::{Logos, SpannedIter};
 use std::iter::Peekable;
 
 pub struct Parser<'input, I>
 where
-    I: Iterator<Item = (Token, std::ops::Range<usize>)>,
+    I: Iterator<Item = (Token, Span)>,
 {
     input: &'input str,
     tokens: Peekable<I>,
-    reserve: Option<(Token, &'input str)>,
+    reserve: Option<(Token, &'input str, Span)>,
+    // span of the token most recently returned by `next`
+    last_span: Span,
+    // errors that were recovered from while parsing
+    errors: Vec<ParseError>,
 }
 
 impl<'input, I> Parser<'input, I>
 where
-    I: Iterator<Item = (Token, std::ops::Range<usize>)>,
+    I: Iterator<Item = (Token, Span)>,
 {
     pub fn peek(&mut self) -> Token {
+        self.skip_lexer_errors();
         match self.reserve {
-            Some((tok, _)) => tok,
+            Some((tok, _, _)) => tok,
             None => self
                 .tokens
                 .peek()
@@ -29,34 +38,117 @@ where
                 .unwrap_or(T![eof]),
         }
     }
+    // span of the next token, or an empty span at the end of the input
+    pub fn peek_span(&mut self) -> Span {
+        self.skip_lexer_errors();
+        match &self.reserve {
+            Some((_, _, span)) => span.clone(),
+            None => {
+                let end = self.input.len();
+                self.tokens
+                    .peek()
+                    .map(|(_, span)| span.clone())
+                    .unwrap_or(end..end)
+            }
+        }
+    }
     pub fn at(&mut self, kind: Token) -> bool {
         self.peek() == kind
     }
+    #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> Option<(Token, &'input str)> {
-        match self.reserve {
-            Some(data) => {
-                self.reserve = None;
-                Some(data)
+        self.skip_lexer_errors();
+        let (token, text, span) = match self.reserve.take() {
+            Some(data) => data,
+            None => {
+                let (token, span) = self.tokens.next()?;
+                (token, &self.input[span.clone()], span)
             }
-            None => self.tokens.next().map(|(token, r)| (token, &self.input[r])),
+        };
+        self.last_span = span;
+        Some((token, text))
+    }
+    pub fn consume(&mut self, expected: Token) -> Result<(), ParseError> {
+        if !self.at(expected) {
+            return Err(self.unexpected(vec![expected]));
+        }
+        self.next();
+        Ok(())
+    }
+    // consumes an identifier and returns its text
+    pub fn ident(&mut self) -> Result<&'input str, ParseError> {
+        if !self.at(T![ident]) {
+            return Err(self.unexpected(vec![T![ident]]));
+        }
+        let (_, text) = self.next().unwrap();
+        Ok(text)
+    }
+    // consumes the name of a variable a value is given to, or `_`
+    pub fn target(&mut self) -> Result<ast::Name, ParseError> {
+        if !self.at(T![ident]) && !self.at(T![_]) {
+            return Err(self.unexpected(vec![T![ident], T![_]]));
         }
+        let (_, text) = self.next().unwrap();
+        Ok(self.name(text))
     }
-    pub fn consume(&mut self, expected: Token) {
-        let (token, _) = self.next().expect(&format!(
-            "Expected to consume `{}`, but there was no next token",
-            expected
-        ));
-        assert_eq!(
-            token, expected,
-            "Expected to consume `{}`, but found `{}`",
-            expected, token
-        );
+    // a name just consumed, with its span
+    fn name(&self, text: &str) -> ast::Name {
+        ast::Name {
+            name: text.to_string(),
+            span: self.last_span.clone(),
+        }
+    }
+    // error for the next token, which is not one of `expected`
+    fn unexpected(&mut self, expected: Vec<Token>) -> ParseError {
+        let found = self.peek();
+        ParseError::new(expected, found, self.peek_span())
     }
+    // puts the token most recently returned by `next` back
     pub fn push(&mut self, data: (Token, &'input str)) {
         if self.reserve.is_some() {
             panic!("Cannot push; reserve is full: {:?}", self.reserve);
         }
-        self.reserve = Some(data);
+        self.reserve = Some((data.0, data.1, self.last_span.clone()));
+    }
+    // consumes only the first character of the next token and leaves the
+    // rest of it as `rest`, e.g. to read `>>` as two `>`
+    fn split_next(&mut self, rest: Token) {
+        let span = self.peek_span();
+        let (_, text) = self.next().unwrap();
+        self.last_span = span.start..span.start + 1;
+        self.reserve = Some((rest, &text[1..], span.start + 1..span.end));
+    }
+    // input the lexer could not make sense of is reported and then ignored,
+    // so the parser never sees an error token
+    fn skip_lexer_errors(&mut self) {
+        while let Some((T![error], span)) = self.tokens.peek() {
+            let span = span.clone();
+            let message = format!("unrecognized token `{}`", &self.input[span.clone()]);
+            self.errors
+                .push(ParseError::with_message(message, T![error], span));
+            self.tokens.next();
+        }
+    }
+    // returns every error recovered from while producing `result`, followed
+    // by the one it failed with, if any
+    fn check_recovered<T>(&mut self, result: Result<T, ParseError>) -> Result<T, Vec<ParseError>> {
+        let mut errors = std::mem::take(&mut self.errors);
+        match result {
+            Ok(value) if errors.is_empty() => Ok(value),
+            Ok(_) => Err(errors),
+            Err(err) => {
+                errors.push(err);
+                Err(errors)
+            }
+        }
+    }
+    // start of the next token, used to begin the span of a node
+    fn start(&mut self) -> usize {
+        self.peek_span().start
+    }
+    // span from `start` up to the end of the last consumed token
+    fn span_from(&self, start: usize) -> Span {
+        start..self.last_span.end.max(start)
     }
 }
 
@@ -66,6 +158,8 @@ impl<'input> Parser<'input, SpannedIter<'input, Token>> {
             input,
             tokens: Token::lexer(input).spanned().peekable(),
             reserve: None,
+            last_span: 0..0,
+            errors: Vec::new(),
         }
     }
 }
@@ -0,0 +1,31 @@
+use std::ops::Range;
+
+// Byte range into the source text, as produced by `Token::lexer(..).spanned()`
+pub type Span = Range<usize>;
+
+// Converts a byte offset into a 1-based (line, column) pair. Columns count
+// characters, not bytes. Offsets past the end of the input are clamped.
+pub fn line_col(source: &str, offset: usize) -> (usize, usize) {
+    let mut offset = offset.min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let before = &source[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let col = before[line_start..].chars().count() + 1;
+    (line, col)
+}
+
+#[test]
+fn line_cols() {
+    let source = "fn main() {\n    foo();\n}\n";
+    assert_eq!(line_col(source, 0), (1, 1));
+    assert_eq!(line_col(source, 3), (1, 4));
+    assert_eq!(line_col(source, 12), (2, 1));
+    assert_eq!(line_col(source, 16), (2, 5));
+    assert_eq!(line_col(source, 23), (3, 1));
+    assert_eq!(line_col(source, 1000), (4, 1));
+    assert_eq!(line_col("é\nb", 3), (2, 1));
+    assert_eq!(line_col("éb", 2), (1, 2));
+}
@@ -0,0 +1,76 @@
+use crate::diagnostic::Diagnostic;
+use crate::interpreter::{Interpreter, RuntimeError};
+use crate::parser::ast;
+
+// Runs the `#[test]` functions of a program. Each test gets a fresh
+// interpreter, so nothing carries over from one test to the next, and
+// `SOURCES` and `SINKS` are stubbed by the interpreter.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestReport {
+    pub results: Vec<(String, Outcome)>,
+    // tests whose name does not match the filter
+    pub filtered_out: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Passed,
+    Failed(RuntimeError),
+    // marked with `#[ignore]`
+    Ignored,
+}
+
+impl TestReport {
+    pub fn count(&self, outcome: fn(&Outcome) -> bool) -> usize {
+        self.results.iter().filter(|(_, o)| outcome(o)).count()
+    }
+
+    pub fn passed(&self) -> bool {
+        self.count(|o| matches!(o, Outcome::Failed(_))) == 0
+    }
+}
+
+// parse and check a program, then run each test whose name contains `filter`
+pub fn run_tests(program: &str, filter: &str) -> Result<TestReport, Vec<Diagnostic>> {
+    let mut items = crate::check_tests(program)?;
+    crate::desugar::desugar(&mut items);
+    let tests: Vec<&ast::Item> = items
+        .iter()
+        .filter(|item| item.has_attribute("test"))
+        .collect();
+    let mut report = TestReport {
+        results: Vec::new(),
+        filtered_out: 0,
+    };
+    for item in tests {
+        let (name, parameters, return_params) = match &item.kind {
+            ast::ItemKind::Function {
+                name,
+                parameters,
+                return_params,
+                ..
+            } => (name, parameters, return_params),
+            ast::ItemKind::Error => continue,
+        };
+        if !name.contains(filter) {
+            report.filtered_out += 1;
+            continue;
+        }
+        let outcome = if item.has_attribute("ignore") {
+            Outcome::Ignored
+        } else if !parameters.is_empty() || !return_params.is_empty() {
+            Outcome::Failed(RuntimeError::invalid(
+                "test functions cannot have inputs or outputs".to_string(),
+                &item.span,
+            ))
+        } else {
+            match Interpreter::new(&items).call(name, Vec::new()) {
+                Ok(_) => Outcome::Passed,
+                Err(err) => Outcome::Failed(err),
+            }
+        };
+        report.results.push((name.clone(), outcome));
+    }
+    Ok(report)
+}
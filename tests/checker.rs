@@ -1,51 +1,860 @@
-use burn::checker::Checker;
+use burn::checker::{CheckError, Checker};
+use burn::parser::ast::{IntSuffix, ItemKind, StmtKind, Type};
 use burn::parser::Parser;
 
-fn run_checker(input: &str) {
+fn run_checker(input: &str) -> Result<(), Vec<CheckError>> {
     let mut parser = Parser::new(input);
-    let items = parser.file();
+    let items = parser.file().unwrap();
     let mut checker = Checker::new(&items);
-    checker.check_all();
+    checker.check_all()
 }
 
 #[test]
 fn check_fn_param_names_unique() {
-    run_checker("fn main(foo u32, bar u32) -> (baz u32) {}");
+    run_checker("fn main(foo u32, bar u32) -> (baz u32) {}").unwrap();
 }
 
 #[test]
-#[should_panic]
 fn check_fn_param_names_not_unique() {
-    run_checker("fn main(foo u32, bar u32) -> (foo u32) {}");
+    let errors = run_checker("fn main(foo u32, bar u32) -> (foo u32) {}").unwrap_err();
+    assert_eq!(
+        errors,
+        vec![CheckError::ParamRedeclared {
+            name: "foo".to_string(),
+            span: 30..33,
+            first: 8..11,
+        }]
+    );
 }
 
 #[test]
 fn check_fn_param_types_valid() {
-    run_checker("fn main(foo stream<stream<u8>>, bar i32) -> (baz u64, buz stream<i64>) {}");
+    run_checker("fn main(foo stream<stream<u8>>, bar i32) -> (baz u64, buz stream<i64>) {}")
+        .unwrap();
+    run_checker("fn main(foo option<u8>) -> (bar option<stream<u8>>) {}").unwrap();
 }
 
 #[test]
-#[should_panic]
 fn check_fn_param_types_invalid() {
-    run_checker("fn main(foo stream<u32>) -> (bar int) {}");
+    let errors = run_checker("fn main(foo stream<u32>) -> (bar int) {}").unwrap_err();
+    assert_eq!(
+        errors,
+        vec![CheckError::UnknownType {
+            name: "int".to_string(),
+            span: 33..36,
+        }]
+    );
+    let errors = run_checker("fn main(foo stream, bar u8<u8>) {}").unwrap_err();
+    assert!(matches!(
+        errors[..],
+        [
+            CheckError::WrongGenericCount {
+                expected: 1,
+                found: 0,
+                ..
+            },
+            CheckError::WrongGenericCount {
+                expected: 0,
+                found: 1,
+                ..
+            },
+        ]
+    ));
 }
 
 #[test]
 fn check_fn_names_unique() {
-    run_checker("fn main() {} fn foo() {}");
+    run_checker("fn main() {} fn foo() {}").unwrap();
 }
 
 #[test]
-#[should_panic]
 fn check_fn_names_not_unique() {
-    run_checker("fn main() {} fn main() {}");
+    let errors = run_checker("fn main() {} fn main() {}").unwrap_err();
+    assert_eq!(
+        errors,
+        vec![CheckError::FnRedeclared {
+            name: "main".to_string(),
+            span: 13..25,
+            first: 0..12,
+        }]
+    );
 }
 
 #[test]
-#[should_panic]
 fn check_no_main() {
-    run_checker("fn foo() {} fn bar() {}");
+    let errors = run_checker("fn foo() {} fn bar() {}").unwrap_err();
+    assert_eq!(errors, vec![CheckError::MainNotFound]);
 }
 
 #[test]
-fn check_fn_arity() {}
+fn check_reports_all_errors() {
+    let errors = run_checker("fn foo(a int, a u8) {} fn foo() {}").unwrap_err();
+    assert_eq!(errors.len(), 4);
+}
+
+#[test]
+fn check_int_literal_range() {
+    run_checker("fn main() { a := 255u8; b := -128i8; c := 127i8; d := 18446744073709551615u64; }")
+        .unwrap();
+    run_checker("fn main() { a := -9223372036854775808i64; b := -0u32; }").unwrap();
+    let errors = run_checker("fn main() { a := 256u8; }").unwrap_err();
+    assert_eq!(
+        errors,
+        vec![CheckError::LiteralOutOfRange {
+            suffix: IntSuffix::U8,
+            span: 17..22,
+        }]
+    );
+    assert_eq!(
+        errors[0].to_string(),
+        "literal out of range for `u8`, the range is `0..=255`"
+    );
+    let errors = run_checker(
+        "fn main(x i8) { if x == -129i8 { foo(128i8, -1u16); } } fn foo(a i8, b u16) {}",
+    )
+    .unwrap_err();
+    assert_eq!(errors.len(), 3);
+    assert_eq!(errors[0].span(), Some(24..30));
+    assert_eq!(
+        errors[0].to_string(),
+        "literal out of range for `i8`, the range is `-128..=127`"
+    );
+    // literals without a suffix must fit the type they are used as
+    run_checker("fn main(x u8) { x = 255; if x < 200 { x += 1; } }").unwrap();
+    let errors = run_checker("fn main() { x := 1u8; x = 300; }").unwrap_err();
+    assert_eq!(
+        errors,
+        vec![CheckError::LiteralOutOfRange {
+            suffix: IntSuffix::U8,
+            span: 26..29,
+        }]
+    );
+    let errors = run_checker("fn main(x i8) { x = x + -200; } fn f() -> (o u16) { return 70000; }")
+        .unwrap_err();
+    assert_eq!(
+        errors,
+        vec![
+            CheckError::LiteralOutOfRange {
+                suffix: IntSuffix::I8,
+                span: 24..28,
+            },
+            CheckError::LiteralOutOfRange {
+                suffix: IntSuffix::U16,
+                span: 59..64,
+            },
+        ]
+    );
+}
+
+#[test]
+fn check_condition_types() {
+    run_checker("fn main() { while true { if false { break; } } }").unwrap();
+    let errors = run_checker("fn main() { if 1 {} while 'yes' {} }").unwrap_err();
+    assert_eq!(
+        errors,
+        vec![
+            CheckError::TypeMismatch {
+                expected: Type::new("bool", vec![]),
+                found: Type::new("{integer}", vec![]),
+                span: 15..16,
+            },
+            CheckError::TypeMismatch {
+                expected: Type::new("bool", vec![]),
+                found: Type::new("stream", vec![Type::new("u8", vec![])]),
+                span: 26..31,
+            },
+        ]
+    );
+    assert_eq!(
+        errors[1].to_string(),
+        "mismatched types: expected `bool`, found `stream<u8>`"
+    );
+}
+
+#[test]
+fn check_return_count() {
+    run_checker(
+        "fn main() {} fn foo() -> (ok bool, msg stream<u8>) { return true, 'ok'; return; return _, 'x'; }",
+    )
+    .unwrap();
+    let errors =
+        run_checker("fn main() { return 1; } fn foo() -> (a u8, b u8) { if true { return 1; } }")
+            .unwrap_err();
+    assert_eq!(
+        errors,
+        vec![
+            CheckError::ReturnCount {
+                expected: 0,
+                found: 1,
+                span: 12..21,
+            },
+            CheckError::ReturnCount {
+                expected: 2,
+                found: 1,
+                span: 61..70,
+            },
+        ]
+    );
+    assert_eq!(errors[1].to_string(), "expected 2 return values, found 1");
+}
+
+#[test]
+fn check_index_types() {
+    run_checker("fn main(s stream<u8>, n u32) { a := s[0]; b := s[1u32..]; c := s[..n]; }")
+        .unwrap();
+    let errors =
+        run_checker("fn main(s stream<u8>) { a := s[true]; b := s['a'..2]; }").unwrap_err();
+    assert_eq!(
+        errors,
+        vec![
+            CheckError::TypeMismatch {
+                expected: Type::new("u32", vec![]),
+                found: Type::new("bool", vec![]),
+                span: 31..35,
+            },
+            CheckError::TypeMismatch {
+                expected: Type::new("u32", vec![]),
+                found: Type::new("stream", vec![Type::new("u8", vec![])]),
+                span: 45..48,
+            },
+        ]
+    );
+}
+
+#[test]
+fn check_options() {
+    run_checker("fn main(s stream<u8>) { if s.next() == none { return; } c := s.get(1)?; }")
+        .unwrap();
+    // a value is given where an option holding it is expected
+    let program = r#"
+        fn first(x u8) -> (o option<u8>) { return x; }
+        fn main(x u8) -> (o option<u8>) { o = x; o = none; y := none; y = 3u8; }
+    "#;
+    run_checker(program).unwrap();
+    let errors = run_checker("fn main(x option<u8>) -> (o u8) { o = x; }").unwrap_err();
+    assert_eq!(
+        errors,
+        vec![CheckError::TypeMismatch {
+            expected: Type::new("u8", vec![]),
+            found: Type::new("option", vec![Type::new("u8", vec![])]),
+            span: 38..39,
+        }]
+    );
+    let errors = run_checker("fn main(a option, b option<u8, u8>) { if none {} }").unwrap_err();
+    assert_eq!(
+        errors,
+        vec![
+            CheckError::WrongGenericCount {
+                name: "option".to_string(),
+                expected: 1,
+                found: 0,
+                span: 10..16,
+            },
+            CheckError::WrongGenericCount {
+                name: "option".to_string(),
+                expected: 1,
+                found: 2,
+                span: 20..34,
+            },
+            CheckError::TypeMismatch {
+                expected: Type::new("bool", vec![]),
+                found: Type::new("option", vec![Type::new("_", vec![])]),
+                span: 41..45,
+            },
+        ]
+    );
+    assert_eq!(
+        errors[2].to_string(),
+        "mismatched types: expected `bool`, found `option<_>`"
+    );
+
+    // an option is used as it is only when tested for a value or written
+    run_checker(
+        "fn main(s stream<u8>) { x := s.next().is_none() || none != s.get(); s.next() -> SINKS::stdout(); }",
+    )
+    .unwrap();
+    let program = r#"fn main(s stream<stream<u8>>) {
+        x := s.next() == 'a'; y := s.next().len(); f(s.next()); g(s.get());
+    } fn f(a stream<u8>) {} fn g(n u32) {}"#;
+    let errors = run_checker(program).unwrap_err();
+    let stream = Type::new("stream", vec![Type::new("u8", vec![])]);
+    let found = Type::new("option", vec![stream]);
+    assert_eq!(
+        errors,
+        vec![45..53, 67..75, 85..93, 98..105]
+            .into_iter()
+            .map(|span| CheckError::OptionNotUnwrapped {
+                found: found.clone(),
+                span,
+            })
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        errors[0].to_string(),
+        "`option<stream<u8>>` must be unwrapped with `?` before its value is used"
+    );
+}
+
+#[test]
+fn check_fan_out() {
+    let program = r#"
+        fn dup(input stream<u8>) -> (a stream<u8>, b stream<u8>) {}
+        fn main(x stream<u8>, y stream<u8>) {
+            dup(SOURCES::stdin()) -> (SINKS::stdout(), SINKS::stderr());
+            SOURCES::stdin() -> dup() -> (x, y);
+            (1, 2) -> (x, y);
+            SOURCES::tcp(80) -> (x, y);
+        }
+    "#;
+    run_checker(program).unwrap();
+    let errors = run_checker(
+        "fn dup() -> (a stream<u8>, b stream<u8>) {} fn main(x stream<u8>, y stream<u8>, z stream<u8>) { dup() -> (x, y, z); 1 -> (x, y); }",
+    )
+    .unwrap_err();
+    assert_eq!(
+        errors,
+        vec![
+            CheckError::FanOutCount {
+                streams: 3,
+                found: 2,
+                span: 96..115,
+            },
+            CheckError::FanOutCount {
+                streams: 2,
+                found: 1,
+                span: 116..128,
+            },
+        ]
+    );
+    assert_eq!(
+        errors[0].to_string(),
+        "expected 3 values to write to 3 streams, found 2"
+    );
+}
+
+#[test]
+fn check_stream_writes() {
+    let program = r#"
+        fn main(out stream<u8>, n u32, lines stream<stream<u8>>) {
+            `a` -> out;
+            'abc' -> out;
+            'abc' -> lines;
+            out.get(0) -> out;
+            ('a', `b`) -> (out, out);
+        }
+    "#;
+    run_checker(program).unwrap();
+    let program =
+        "fn main(out stream<u8>, n u32) { true -> out; 'abc' -> n; (true, `a`) -> (out, n); }";
+    let errors = run_checker(program).unwrap_err();
+    let u8 = || Type::new("u8", vec![]);
+    assert_eq!(
+        errors,
+        vec![
+            CheckError::TypeMismatch {
+                expected: u8(),
+                found: Type::new("bool", vec![]),
+                span: 33..37,
+            },
+            CheckError::TypeMismatch {
+                expected: Type::new("stream", vec![Type::new("_", vec![])]),
+                found: Type::new("u32", vec![]),
+                span: 55..56,
+            },
+            CheckError::TypeMismatch {
+                expected: u8(),
+                found: Type::new("bool", vec![]),
+                span: 59..63,
+            },
+            CheckError::TypeMismatch {
+                expected: Type::new("stream", vec![Type::new("_", vec![])]),
+                found: Type::new("u32", vec![]),
+                span: 79..80,
+            },
+        ]
+    );
+}
+
+#[test]
+fn check_methods() {
+    let program = r#"
+        fn main(s stream<u8>, n u32) -> (o option<u8>) {
+            a := s.get(n);
+            b := s.get();
+            c := s.consume(2).chain('x');
+            d := s.split(' ').next()?.parse() + n;
+            e := n.ascii().len();
+            f := a.is_some();
+        }
+    "#;
+    run_checker(program).unwrap();
+    let errors = run_checker(
+        "fn main(s stream<u8>) { s.foo(1, 2); s.get(true); s.consume('x'); SOURCES::stdin().bogus(); s.len(1); }",
+    )
+    .unwrap_err();
+    let stream = Type::new("stream", vec![Type::new("u8", vec![])]);
+    assert_eq!(
+        errors,
+        vec![
+            CheckError::MethodNotFound {
+                method: "foo".to_string(),
+                receiver: stream.clone(),
+                span: 24..35,
+            },
+            CheckError::TypeMismatch {
+                expected: Type::new("u32", vec![]),
+                found: Type::new("bool", vec![]),
+                span: 43..47,
+            },
+            CheckError::TypeMismatch {
+                expected: Type::new("u32", vec![]),
+                found: stream.clone(),
+                span: 60..63,
+            },
+            CheckError::MethodNotFound {
+                method: "bogus".to_string(),
+                receiver: stream.clone(),
+                span: 66..90,
+            },
+            CheckError::ArgCount {
+                name: "len".to_string(),
+                expected: 0,
+                found: 1,
+                span: 92..100,
+            },
+        ]
+    );
+    assert_eq!(errors[0].to_string(), "no method `foo` for `stream<u8>`");
+    // only the inputs of functions in the program parse a stream of bytes
+    run_checker("fn main(s stream<stream<u8>>) { f(s.get()?); } fn f(n u32) {}").unwrap();
+    // a value piped into a method is its last argument
+    run_checker("fn main(s stream<u8>) { x := 'x' -> s.chain(); }").unwrap();
+    let errors = run_checker("fn main(s stream<u8>) { x := true -> s.chain(); }").unwrap_err();
+    assert_eq!(
+        errors,
+        vec![CheckError::TypeMismatch {
+            expected: stream,
+            found: Type::new("bool", vec![]),
+            span: 29..33,
+        }]
+    );
+}
+
+#[test]
+fn check_scopes() {
+    let program = r#"
+        fn main(s stream<u8>) -> (n u32) {
+            x := 1;
+            n = x;
+            { x := 'shadowed'; x -> SINKS::stdout(); }
+            for b in s { if b == `a` { n++; x := b; } }
+            while false { y := 2; }
+            y := x;
+        }
+    "#;
+    run_checker(program).unwrap();
+    let errors =
+        run_checker("fn main(s stream<u8>) { a := 1; a := 2; b = a; for c in s { c++; } c--; d; }")
+            .unwrap_err();
+    assert_eq!(
+        errors,
+        vec![
+            CheckError::VarRedeclared {
+                name: "a".to_string(),
+                span: 32..33,
+                first: 24..25,
+            },
+            CheckError::UndefinedName {
+                name: "b".to_string(),
+                span: 40..41,
+            },
+            CheckError::UndefinedName {
+                name: "c".to_string(),
+                span: 67..68,
+            },
+            CheckError::UndefinedName {
+                name: "d".to_string(),
+                span: 72..73,
+            },
+        ]
+    );
+    assert_eq!(errors[0].to_string(), "redeclaration of variable name `a`");
+    assert_eq!(errors[3].to_string(), "cannot find `d` in this scope");
+    let errors = run_checker("fn main(a u8) -> (b u8) { a := 1; b := 2; }").unwrap_err();
+    assert_eq!(errors.len(), 2);
+}
+
+#[test]
+fn check_name_resolution() {
+    // `x` at 30, 40 and 48 is the parameter; `x` at 55 is the block's own
+    let input = "fn main(x u8) -> (y u8) { y = x; { z := x; x := x; y = x; } }";
+    let mut parser = Parser::new(input);
+    let items = parser.file().unwrap();
+    let mut checker = Checker::new(&items);
+    checker.check_all().unwrap();
+    let param = checker.resolve(&(30..31)).unwrap();
+    assert_eq!(checker.symbol(param).name, "x");
+    assert_eq!(checker.symbol(param).span, 8..9);
+    assert_eq!(checker.resolve(&(40..41)), Some(param));
+    assert_eq!(checker.resolve(&(48..49)), Some(param));
+    let local = checker.resolve(&(55..56)).unwrap();
+    assert_ne!(local, param);
+    assert_eq!(checker.symbol(local).span, 43..44);
+
+    // each name a value is destructured to resolves on its own
+    let input = "fn f() -> (a u8, b u8) {} fn main(x u8, y u8) { x, y = f(); z, w := f(); }";
+    let mut parser = Parser::new(input);
+    let items = parser.file().unwrap();
+    let mut checker = Checker::new(&items);
+    checker.check_all().unwrap();
+    let x = checker.resolve(&(48..49)).unwrap();
+    let y = checker.resolve(&(51..52)).unwrap();
+    assert_eq!(checker.symbol(x).span, 34..35);
+    assert_eq!(checker.symbol(y).span, 40..41);
+}
+
+#[test]
+fn check_expr_types() {
+    let program = r#"
+        fn pair(a u8) -> (x u8, y stream<u8>) { return a * 2, 'ab'.chain('c'); }
+        fn main(s stream<u8>) -> (n u32, found bool) {
+            n = s.len() + 1;
+            found = s.next()? == `a` || !s.eof() && n >= 2;
+            x, y := pair(s.get(0)?);
+            for item in y.split(' ') { item.next()? + x -> SINKS::stdout(); }
+            z := s[1..].consume(2)[0];
+            found = z.is_some() ^ true;
+            return;
+        }
+    "#;
+    run_checker(program).unwrap();
+    let errors = run_checker(
+        "fn main(s stream<u8>, b u8) { x := b + true; y := s == 5u32; z := !b; w := b?; b = 1u16; }",
+    )
+    .unwrap_err();
+    let u8 = Type::new("u8", vec![]);
+    assert_eq!(
+        errors,
+        vec![
+            CheckError::TypeMismatch {
+                expected: u8.clone(),
+                found: Type::new("bool", vec![]),
+                span: 39..43,
+            },
+            CheckError::TypeMismatch {
+                expected: Type::new("stream", vec![u8.clone()]),
+                found: Type::new("u32", vec![]),
+                span: 55..59,
+            },
+            CheckError::TypeMismatch {
+                expected: Type::new("bool", vec![]),
+                found: u8.clone(),
+                span: 67..68,
+            },
+            CheckError::TypeMismatch {
+                expected: Type::new("option", vec![Type::new("_", vec![])]),
+                found: u8.clone(),
+                span: 75..76,
+            },
+            CheckError::TypeMismatch {
+                expected: u8,
+                found: Type::new("u16", vec![]),
+                span: 83..87,
+            },
+        ]
+    );
+    assert_eq!(
+        errors[1].to_string(),
+        "mismatched types: expected `stream<u8>`, found `u32`"
+    );
+    let errors = run_checker("fn main() -> (a u8) { return 'no'; }").unwrap_err();
+    assert_eq!(
+        errors[0].to_string(),
+        "mismatched types: expected `u8`, found `stream<u8>`"
+    );
+}
+
+#[test]
+fn check_loop_control() {
+    run_checker("fn main(s stream<u8>) { for c in s { if c == ` ` { continue; } break; } }")
+        .unwrap();
+    run_checker("fn main() { while true { { break; } } }").unwrap();
+    let errors = run_checker("fn main() { break; if true { continue; } }").unwrap_err();
+    assert_eq!(
+        errors,
+        vec![
+            CheckError::OutsideLoop {
+                keyword: "break",
+                span: 12..18,
+            },
+            CheckError::OutsideLoop {
+                keyword: "continue",
+                span: 29..38,
+            },
+        ]
+    );
+    assert_eq!(errors[0].to_string(), "`break` outside of a loop");
+}
+
+#[test]
+fn check_postfix_operators() {
+    let errors = run_checker("fn main(n u32) { x := n!; }").unwrap_err();
+    assert_eq!(
+        errors,
+        vec![CheckError::PostfixUnsupported {
+            op: "!".to_string(),
+            span: 23..24,
+        }]
+    );
+    assert_eq!(errors[0].to_string(), "postfix `!` is not supported");
+}
+
+#[test]
+fn check_type_table() {
+    let input =
+        "fn two() -> (a u8, b bool) {} fn main(s stream<u8>) { x := s.next(); y, _ := two(); }";
+    let mut parser = Parser::new(input);
+    let items = parser.file().unwrap();
+    let mut checker = Checker::new(&items);
+    checker.check_all().unwrap();
+    let main = match &items[1].kind {
+        ItemKind::Function { body, .. } => body,
+        _ => unreachable!(),
+    };
+    let values: Vec<String> = main
+        .iter()
+        .map(|stmt| match &stmt.kind {
+            StmtKind::Declaration { value, .. } => checker.type_of(value).unwrap().to_string(),
+            _ => unreachable!(),
+        })
+        .collect();
+    assert_eq!(values, vec!["option<u8>", "(u8, bool)"]);
+}
+
+#[test]
+fn check_destructuring() {
+    let program = r#"
+        fn unmix(input stream<u8>) -> (a stream<u8>, b bool) {}
+        fn main(s stream<u8>) {
+            x, y := unmix(s);
+            x, _ = unmix(s);
+            _, _ := unmix(x);
+            y = !y;
+            c, d := (1, 'cd');
+            a, b := SOURCES::tcp(80);
+        }
+    "#;
+    run_checker(program).unwrap();
+    let errors = run_checker(
+        "fn f() -> (a u8, b u8) {} fn main() { x := f(); a, b, c := f(); a, _ := f(); _, b = 1u16; d, e := 1; }",
+    )
+    .unwrap_err();
+    assert_eq!(errors.len(), 5);
+    assert_eq!(
+        errors[0],
+        CheckError::TupleAssigned {
+            found: 2,
+            span: 38..47,
+        }
+    );
+    assert_eq!(
+        errors[0].to_string(),
+        "cannot assign 2 values to a single variable, as there is no tuple type"
+    );
+    assert_eq!(
+        errors[1],
+        CheckError::DestructureCount {
+            names: 3,
+            found: 2,
+            span: 48..63,
+        }
+    );
+    assert_eq!(errors[1].to_string(), "cannot assign 2 values to 3 names");
+    // `a` is declared by the first destructuring, even though it failed
+    assert_eq!(
+        errors[2],
+        CheckError::VarRedeclared {
+            name: "a".to_string(),
+            span: 64..65,
+            first: 48..49,
+        }
+    );
+    assert_eq!(errors[3].to_string(), "cannot assign 1 value to 2 names");
+    assert_eq!(errors[4].to_string(), "cannot assign 1 value to 2 names");
+}
+
+#[test]
+fn check_fn_arity() {
+    let program = r#"
+        fn main(s stream<u8>) {
+            x := add(1, 2);
+            y := pair() -> add();
+            z := 3 -> add(_, 4);
+            wrap(x, 1u64);
+            s -> later();
+            SOURCES::stdin() -> later();
+        }
+        fn add(a u8, b u8) -> (c u8) { return a + b; }
+        fn pair() -> (a u8, b u8) { return 1, 2; }
+        fn wrap(a option<u8>, n u64) {}
+        fn later(s stream<u8>) {}
+    "#;
+    run_checker(program).unwrap();
+    let errors = run_checker(
+        "fn f(a u8, b bool) {} fn main() { f(1); f(1, 2); 1 -> f(_, _); g(); f(_, true); (1, 2) -> f(); }",
+    )
+    .unwrap_err();
+    let mismatch = |span| CheckError::TypeMismatch {
+        expected: Type::new("bool", vec![]),
+        found: Type::new("{integer}", vec![]),
+        span,
+    };
+    assert_eq!(
+        errors,
+        vec![
+            CheckError::ArgCount {
+                name: "f".to_string(),
+                expected: 2,
+                found: 1,
+                span: 34..38,
+            },
+            mismatch(45..46),
+            CheckError::UnfilledPlaceholder { span: 59..60 },
+            CheckError::FnNotFound {
+                name: "g".to_string(),
+                span: 63..66,
+            },
+            CheckError::UnfilledPlaceholder { span: 70..71 },
+            mismatch(80..86),
+        ]
+    );
+    assert_eq!(errors[0].to_string(), "`f` takes 2 arguments, found 1");
+    assert_eq!(errors[3].to_string(), "cannot find function `g`");
+    // integers are not converted from one type to another when passed
+    let errors =
+        run_checker("fn f(a u8) {} fn main(x u32, y i8) { f(x); f(y); f(70000u32); f(200); }")
+            .unwrap_err();
+    let u8 = || Type::new("u8", vec![]);
+    assert_eq!(
+        errors,
+        vec![
+            CheckError::TypeMismatch {
+                expected: u8(),
+                found: Type::new("u32", vec![]),
+                span: 39..40,
+            },
+            CheckError::TypeMismatch {
+                expected: u8(),
+                found: Type::new("i8", vec![]),
+                span: 45..46,
+            },
+            CheckError::TypeMismatch {
+                expected: u8(),
+                found: Type::new("u32", vec![]),
+                span: 51..59,
+            },
+        ]
+    );
+    // `_` stands for a value only as an argument or a returned value
+    run_checker("fn main() -> (a u8, b u8) { a = 1; return _, 2; }").unwrap();
+    let errors =
+        run_checker("fn main(s stream<u8>) { x := _; y := _ + 1; if _ {} s.get(_); }").unwrap_err();
+    assert_eq!(
+        errors,
+        vec![
+            CheckError::UnfilledPlaceholder { span: 29..30 },
+            CheckError::UnfilledPlaceholder { span: 37..38 },
+            CheckError::UnfilledPlaceholder { span: 47..48 },
+            CheckError::UnfilledPlaceholder { span: 58..59 },
+        ]
+    );
+}
+
+#[test]
+fn check_builtins() {
+    let program = r#"
+        fn main() {
+            input, output := SOURCES::tcp(8080);
+            found, data := SOURCES::file(SOURCES::raw_args());
+            for arg in SOURCES::args() { arg.next()? -> output; }
+            input -> SINKS::file('log');
+            SOURCES::stdin().skip(1) -> SINKS::stdout();
+            SOURCES::tcp(80) -> (SINKS::stdout(), SINKS::stderr());
+            `!` -> SINKS::stderr();
+        }
+    "#;
+    run_checker(program).unwrap();
+    let errors = run_checker(
+        "fn main() { SOURCES::stdn(); x, _ := SOURCES::tcp(true); SINKS::stdout(true); SINKS::stdout(1, 2); y := SOURCES::file('a'); BOGUS::thing(); }",
+    )
+    .unwrap_err();
+    let bool = Type::new("bool", vec![]);
+    assert_eq!(
+        errors,
+        vec![
+            CheckError::BuiltinNotFound {
+                path: "SOURCES::stdn".to_string(),
+                suggestion: Some("SOURCES::stdin".to_string()),
+                span: 12..27,
+            },
+            CheckError::TypeMismatch {
+                expected: Type::new("u16", vec![]),
+                found: bool.clone(),
+                span: 50..54,
+            },
+            CheckError::ArgCount {
+                name: "SINKS::stdout".to_string(),
+                expected: 0,
+                found: 1,
+                span: 57..76,
+            },
+            CheckError::ArgCount {
+                name: "SINKS::stdout".to_string(),
+                expected: 0,
+                found: 2,
+                span: 78..97,
+            },
+            CheckError::TupleAssigned {
+                found: 2,
+                span: 99..123,
+            },
+            CheckError::BuiltinNotFound {
+                path: "BOGUS::thing".to_string(),
+                suggestion: None,
+                span: 124..138,
+            },
+        ]
+    );
+    assert_eq!(errors[0].to_string(), "cannot find `SOURCES::stdn`");
+    // a function of a namespace named without calling it must exist too
+    // and is only suggested when it is a few edits away
+    let errors = run_checker("fn main() { x := SOURCES::foo; y := SINKS::stdot; }").unwrap_err();
+    assert_eq!(
+        errors,
+        vec![
+            CheckError::BuiltinNotFound {
+                path: "SOURCES::foo".to_string(),
+                suggestion: None,
+                span: 17..29,
+            },
+            CheckError::BuiltinNotFound {
+                path: "SINKS::stdot".to_string(),
+                suggestion: Some("SINKS::stdout".to_string()),
+                span: 36..48,
+            },
+        ]
+    );
+    // the data written to a sink must fit the stream it gives
+    let errors = run_checker("fn main() { true -> SINKS::stdout(); }").unwrap_err();
+    assert_eq!(
+        errors,
+        vec![CheckError::TypeMismatch {
+            expected: Type::new("u8", vec![]),
+            found: bool,
+            span: 12..16,
+        }]
+    );
+    // and a write to a sink is a statement of its own
+    let errors = run_checker("fn main() { x := `a` -> SINKS::stdout(); }").unwrap_err();
+    assert_eq!(errors, vec![CheckError::WriteNotStatement { span: 17..39 }]);
+}
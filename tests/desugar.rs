@@ -0,0 +1,79 @@
+use burn::desugar::desugar;
+use burn::parser::ast::{ItemKind, StmtKind};
+use burn::parser::Parser;
+
+// desugar the statement `input;` and show its expression
+fn desugared(input: &str) -> String {
+    let program = format!("fn main() {{ {}; }}", input);
+    let mut parser = Parser::new(&program);
+    let mut items = parser.file().unwrap();
+    desugar(&mut items);
+    match &items[0].kind {
+        ItemKind::Function { body, .. } => match &body[0].kind {
+            StmtKind::Expr(expr) => expr.to_string(),
+            StmtKind::StreamWrite { value, stream } => format!("{} -> {}", value, stream),
+            _ => unreachable!(),
+        },
+        ItemKind::Error => unreachable!(),
+    }
+}
+
+#[test]
+fn desugar_pipes() {
+    assert_eq!(desugared("foo -> bar()"), "bar(foo)");
+    assert_eq!(desugared("foo() -> bar('baz')"), "bar(\"baz\", foo())");
+    assert_eq!(desugared("1+2*3 -> foo()"), "foo((1 + (2 * 3)))");
+    assert_eq!(desugared("foo() -> bar() -> baz()"), "baz(bar(foo()))");
+    assert_eq!(
+        desugared("foo() -> bar() -> baz() -> buzz()"),
+        "buzz(baz(bar(foo())))"
+    );
+    assert_eq!(desugared("1 -> foo() < -10"), "(foo(1) < (-10))");
+    assert_eq!(desugared("x -> f() -> out"), "f(x) -> out");
+}
+
+#[test]
+fn desugar_placeholders() {
+    assert_eq!(
+        desugared("('hello', 'world') -> mix()"),
+        "mix(\"hello\", \"world\")"
+    );
+    assert_eq!(
+        desugared("'world' -> mix('hello', _)"),
+        "mix(\"hello\", \"world\")"
+    );
+    assert_eq!(
+        desugared("'hello' -> mix(_, 'world')"),
+        "mix(\"hello\", \"world\")"
+    );
+    assert_eq!(
+        desugared("('hello', 'world') -> mix(_, foo, _)"),
+        "mix(\"hello\", foo, \"world\")"
+    );
+    assert_eq!(
+        desugared("('hello', 'world', bar) -> mix(_, foo, _)"),
+        "mix(\"hello\", foo, \"world\", bar)"
+    );
+}
+
+#[test]
+fn desugar_method_calls() {
+    assert_eq!(desugared("1 -> foo.bar().baz()"), "foo.bar().baz(1)");
+    assert_eq!(desugared("1 -> foo::bar(_, 2)"), "foo::bar(_, 2, 1)");
+    assert_eq!(
+        desugared("'log' -> SOURCES::file()"),
+        "SOURCES::file(\"log\")"
+    );
+}
+
+#[test]
+fn desugar_sinks() {
+    assert_eq!(desugared("x -> SINKS::stdout()"), "x -> SINKS::stdout()");
+    assert_eq!(
+        desugared("x -> f() -> SINKS::file('log')"),
+        "f(x) -> SINKS::file(\"log\")"
+    );
+    // only a sink is written to
+    assert_eq!(desugared("x -> SINKS::stdot()"), "SINKS::stdot(x)");
+    assert_eq!(desugared("x -> SOURCES::stdin()"), "SOURCES::stdin(x)");
+}
@@ -0,0 +1,131 @@
+use burn::compile;
+use burn::diagnostic::Diagnostic;
+
+fn render(input: &str) -> Vec<String> {
+    compile(input)
+        .unwrap_err()
+        .iter()
+        .map(|diagnostic| diagnostic.render("test.burn", input))
+        .collect()
+}
+
+#[test]
+fn render_parse_error() {
+    let input = "fn main() {\n    x := 1\n}\n";
+    assert_eq!(
+        render(input),
+        vec![
+            "error[E0002]: expected `;`, found `}`
+ --> test.burn:3:1
+  |
+3 | }
+  | ^
+"
+        ]
+    );
+}
+
+#[test]
+fn render_lexer_error() {
+    let input = "fn main() {\n\tx := 1 $ 2;\n}\n";
+    assert_eq!(
+        render(input)[0],
+        "error[E0001]: unrecognized token `$`
+ --> test.burn:2:9
+  |
+2 | \tx := 1 $ 2;
+  | \t       ^
+"
+    );
+}
+
+#[test]
+fn render_secondary_label() {
+    let input = "fn main() {}\n\nfn foo() {\n}\n\n\n\n\n\n\nfn foo() {}\n";
+    assert_eq!(
+        render(input),
+        vec![
+            "error[E0100]: redeclaration of function name `foo`
+  --> test.burn:11:1
+   |
+ 3 | fn foo() {
+   | ---------- first declared here
+...
+11 | fn foo() {}
+   | ^^^^^^^^^^^
+"
+        ]
+    );
+
+    let input = "fn main(a u8,\n        b u8,\n        a u8) {}";
+    assert_eq!(
+        render(input),
+        vec![
+            "error[E0101]: redeclaration of parameter name `a`
+ --> test.burn:3:9
+  |
+1 | fn main(a u8,
+  |         - first declared here
+2 |         b u8,
+3 |         a u8) {}
+  |         ^
+"
+        ]
+    );
+}
+
+#[test]
+fn render_suggestion() {
+    let input = "fn main() {\n    SINKS::stdot('hi');\n}\n";
+    assert_eq!(
+        render(input),
+        vec![
+            "error[E0106]: cannot find `SINKS::stdot`
+ --> test.burn:2:5
+  |
+2 |     SINKS::stdot('hi');
+  |     ^^^^^^^^^^^^^^^^^^
+  = help: did you mean `SINKS::stdout`?
+"
+        ]
+    );
+}
+
+#[test]
+fn render_without_span() {
+    assert_eq!(
+        render("fn foo() {}"),
+        vec!["error[E0102]: `main` function not found\n --> test.burn\n"]
+    );
+}
+
+#[test]
+fn render_many_errors() {
+    let input = "fn main() {\n    x := ;\n    y := 1 +;\n}\nfn (a u8) {}\n";
+    let rendered = render(input);
+    assert_eq!(rendered.len(), 3);
+    assert!(rendered[0].contains("test.burn:2:10"));
+    assert!(rendered[1].contains("test.burn:3:13"));
+    assert!(rendered[2].contains("test.burn:5:4"));
+}
+
+#[test]
+fn render_runtime_error() {
+    let input = "fn main() {}\nfn f() {\n    assert 'ab' == 'ac';\n}\n";
+    let items = burn::check(input).unwrap();
+    let err = burn::interpreter::Interpreter::new(&items)
+        .call("f", vec![])
+        .unwrap_err();
+    assert_eq!(
+        Diagnostic::from(&err).render("test.burn", input),
+        "error: assertion `left == right` failed
+ --> test.burn:3:5
+  |
+3 |     assert 'ab' == 'ac';
+  |     ^^^^^^^^^^^^^^^^^^^^
+  = index: 1
+  = left: \"ab\"
+  = right: \"ac\"
+"
+    );
+}
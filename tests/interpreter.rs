@@ -0,0 +1,322 @@
+use burn::desugar::desugar;
+use burn::interpreter::{Interpreter, RuntimeError, Value};
+use burn::parser::Parser;
+
+fn run(input: &str, name: &str, args: Vec<Value>) -> Result<Vec<Value>, RuntimeError> {
+    let mut parser = Parser::new(input);
+    let mut items = parser.file().unwrap();
+    desugar(&mut items);
+    let mut interpreter = Interpreter::new(&items);
+    interpreter.call(name, args)
+}
+
+#[test]
+fn run_stream_functions() {
+    let rot13 = r#"
+        fn rot13(input stream<u8>) -> (out stream<u8>) {
+            for byte in input {
+                if byte >= `a` && byte <= `m` || byte >= `A` && byte <= `M` {
+                    byte + 13 -> out;
+                } else if byte >= `n` && byte <= `z` || byte >= `N` && byte <= `Z` {
+                    byte - 13 -> out;
+                } else {
+                    byte -> out;
+                }
+            }
+        }
+    "#;
+    let outputs = run(rot13, "rot13", vec![Value::bytes(b"foo bar")]).unwrap();
+    assert_eq!(outputs, vec![Value::bytes(b"sbb one")]);
+
+    let mix = r#"
+        fn mix(a stream<u8>, b stream<u8>) -> (out stream<u8>) {
+            while !a.eof() && !b.eof() {
+                a.next() -> out;
+                b.next() -> out;
+            }
+        }
+    "#;
+    let outputs = run(mix, "mix", vec![Value::bytes(b"foo"), Value::bytes(b"bar")]).unwrap();
+    assert_eq!(outputs, vec![Value::bytes(b"fboaor")]);
+
+    let dup = r#"
+        fn dup(input stream<u8>) -> (out1 stream<u8>, out2 stream<u8>) {
+            for data in input {
+                data -> out1;
+                data -> out2;
+            }
+        }
+    "#;
+    let outputs = run(dup, "dup", vec![Value::bytes(b"foo")]).unwrap();
+    assert_eq!(outputs, vec![Value::bytes(b"foo"), Value::bytes(b"foo")]);
+}
+
+#[test]
+fn run_values() {
+    let program = r#"
+        fn fibonacci(n u32) -> (out u32) {
+            if n <= 1 {
+                return 1;
+            }
+            return fibonacci(n-1) + fibonacci(n-2);
+        }
+        fn count(s stream<u8>) -> (n u32, spaces u32) {
+            for c in s {
+                n++;
+                if c == ` ` {
+                    spaces += 1;
+                }
+            }
+        }
+        fn parse(input stream<u8>) -> (found bool, out stream<u8>) {
+            words := input.split(' ');
+            if words.next()? != 'GET' {
+                return false, 'unsupported';
+            }
+            return true, words.next()?.skip(1);
+        }
+    "#;
+    let outputs = run(program, "fibonacci", vec![Value::Int(10)]).unwrap();
+    assert_eq!(outputs, vec![Value::Int(89)]);
+    // a stream of bytes given to an integer input is parsed as a number
+    let outputs = run(program, "fibonacci", vec![Value::bytes(b"10")]).unwrap();
+    assert_eq!(outputs, vec![Value::Int(89)]);
+    let err = run(program, "fibonacci", vec![Value::bytes(b"ten")]).unwrap_err();
+    assert_eq!(err.to_string(), "cannot parse \"ten\" as a number");
+    let outputs = run(program, "count", vec![Value::bytes(b"a b c")]).unwrap();
+    assert_eq!(outputs, vec![Value::Int(5), Value::Int(2)]);
+    let outputs = run(program, "parse", vec![Value::bytes(b"GET /index.html")]).unwrap();
+    assert_eq!(
+        outputs,
+        vec![Value::Bool(true), Value::bytes(b"index.html")]
+    );
+    let outputs = run(program, "parse", vec![Value::bytes(b"PUT /")]).unwrap();
+    assert_eq!(
+        outputs,
+        vec![Value::Bool(false), Value::bytes(b"unsupported")]
+    );
+}
+
+#[test]
+fn run_asserts() {
+    let program = r#"
+        fn shift(input stream<u8>) -> (out stream<u8>) {
+            for byte in input {
+                byte + 1 -> out;
+            }
+        }
+        fn passes() {
+            assert shift('foo') == 'gpp';
+            assert 1 + 1 == 2;
+            assert true, 'never shown';
+        }
+        fn differs() {
+            assert shift('abc') == 'bcd', 'shifted by one';
+            assert shift('foo') == 'gpq';
+        }
+        fn too_short() {
+            assert 'foo' == 'foobar';
+        }
+        fn is_false() {
+            x := 3;
+            assert x < 2;
+        }
+    "#;
+    assert_eq!(run(program, "passes", vec![]), Ok(vec![]));
+
+    let err = run(program, "differs", vec![]).unwrap_err();
+    assert_eq!(
+        err,
+        RuntimeError::AssertNotEqual {
+            left: "\"gpp\"".to_string(),
+            right: "\"gpq\"".to_string(),
+            index: Some(2),
+            message: None,
+            span: 387..416,
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "assertion `left == right` failed\n index: 2\n  left: \"gpp\"\n right: \"gpq\""
+    );
+
+    let err = run(program, "too_short", vec![]).unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("assertion `left == right` failed\n index: 3"));
+
+    let err = run(program, "is_false", vec![]).unwrap_err();
+    assert_eq!(err.to_string(), "assertion failed: `(x < 2)`");
+
+    let program = "fn f() { assert 1 == 2, 'numbers differ'; }";
+    let err = run(program, "f", vec![]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "assertion `left == right` failed: numbers differ\n  left: 1\n right: 2"
+    );
+}
+
+#[test]
+fn run_index() {
+    let program = r#"
+        fn first(s stream<u8>) -> (out u8) {
+            return s[0];
+        }
+        fn slices(s stream<u8>) -> (a stream<u8>, b stream<u8>, c stream<u8>, len u32) {
+            return s[1..3], s[..2], s[3..], s.len();
+        }
+        fn past_end(s stream<u8>) -> (out option<u8>) {
+            return s[3];
+        }
+        fn bad_range(s stream<u8>) -> (out stream<u8>) {
+            return s[2..1];
+        }
+    "#;
+    let outputs = run(program, "first", vec![Value::bytes(b"GET")]).unwrap();
+    assert_eq!(outputs, vec![Value::Byte(b'G')]);
+    // slicing does not consume the stream
+    let outputs = run(program, "slices", vec![Value::bytes(b"hello")]).unwrap();
+    assert_eq!(
+        outputs,
+        vec![
+            Value::bytes(b"el"),
+            Value::bytes(b"he"),
+            Value::bytes(b"lo"),
+            Value::Int(5)
+        ]
+    );
+    let outputs = run(program, "past_end", vec![Value::bytes(b"abc")]).unwrap();
+    assert_eq!(outputs, vec![Value::None]);
+    let err = run(program, "bad_range", vec![Value::bytes(b"abc")]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "range `2..1` is out of bounds for a stream of 3 items"
+    );
+}
+
+#[test]
+fn run_options() {
+    let program = r#"
+        fn first(s stream<u8>) -> (out option<u8>) {
+            return s.next();
+        }
+        fn pairs(s stream<u8>) -> (out stream<u8>, count u32, rest option<u8>) {
+            rest = s.get(0);
+            while true {
+                a := s.next()?;
+                b := s.next()?;
+                a + b -> out;
+                count++;
+            }
+        }
+        fn unmix(input stream<u8>) -> (a stream<u8>, b stream<u8>) {
+            while !input.eof() {
+                input.next() -> a;
+                input.next() -> b;
+            }
+        }
+        fn tests(s stream<u8>) -> (has_value bool, ended bool, eq bool) {
+            x := s.next();
+            return x.is_some(), s.next().is_none(), s.next() == none;
+        }
+    "#;
+    let outputs = run(program, "first", vec![Value::bytes(b"a")]).unwrap();
+    assert_eq!(outputs, vec![Value::Byte(b'a')]);
+    let outputs = run(program, "first", vec![Value::bytes(b"")]).unwrap();
+    assert_eq!(outputs, vec![Value::None]);
+    // `?` returns from `pairs` at the end of the stream
+    let outputs = run(program, "pairs", vec![Value::bytes(&[1, 2, 3, 4, 5])]).unwrap();
+    assert_eq!(
+        outputs,
+        vec![Value::bytes(&[3, 7]), Value::Int(2), Value::None]
+    );
+    // writing `none` to a stream does nothing
+    let outputs = run(program, "unmix", vec![Value::bytes(b"abc")]).unwrap();
+    assert_eq!(outputs, vec![Value::bytes(b"ac"), Value::bytes(b"b")]);
+    let outputs = run(program, "tests", vec![Value::bytes(b"a")]).unwrap();
+    assert_eq!(
+        outputs,
+        vec![Value::Bool(true), Value::Bool(true), Value::Bool(true)]
+    );
+}
+
+#[test]
+fn run_fan_out() {
+    let program = r#"
+        fn dup(input stream<u8>) -> (out1 stream<u8>, out2 stream<u8>) {
+            for data in input {
+                data -> out1;
+                data -> out2;
+            }
+        }
+        fn split(input stream<u8>) -> (a stream<u8>, b stream<u8>) {
+            input -> dup() -> (a, b);
+        }
+        fn pair() -> (a stream<u8>, b stream<u8>) {
+            (1, 2) -> (a, b);
+            ('xy', 3) -> (a, b);
+        }
+        fn sinks(input stream<u8>) {
+            dup(input) -> (SINKS::stdout(), SINKS::stderr());
+        }
+        fn too_few(input stream<u8>) -> (a stream<u8>, b stream<u8>, c stream<u8>) {
+            dup(input) -> (a, b, c);
+        }
+    "#;
+    let outputs = run(program, "split", vec![Value::bytes(b"foo")]).unwrap();
+    assert_eq!(outputs, vec![Value::bytes(b"foo"), Value::bytes(b"foo")]);
+    let outputs = run(program, "pair", vec![]).unwrap();
+    assert_eq!(
+        outputs,
+        vec![Value::bytes(b"\x01xy"), Value::bytes(&[2, 3])]
+    );
+    run(program, "sinks", vec![Value::bytes(b"foo")]).unwrap();
+    let err = run(program, "too_few", vec![Value::bytes(b"foo")]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "expected 3 values to write to 3 streams, found 2"
+    );
+}
+
+#[test]
+fn run_destructuring() {
+    let program = r#"
+        fn unmix(input stream<u8>) -> (a stream<u8>, b stream<u8>) {
+            while true {
+                input.next()? -> a;
+                input.next()? -> b;
+            }
+        }
+        fn evens(input stream<u8>) -> (out stream<u8>) {
+            out, _ := unmix(input);
+        }
+        fn odds(input stream<u8>) -> (out stream<u8>) {
+            _, _ = unmix(input);
+            _, out = unmix('ab');
+        }
+    "#;
+    let outputs = run(program, "evens", vec![Value::bytes(b"abcd")]).unwrap();
+    assert_eq!(outputs, vec![Value::bytes(b"ac")]);
+    let outputs = run(program, "odds", vec![Value::bytes(b"abcd")]).unwrap();
+    assert_eq!(outputs, vec![Value::bytes(b"b")]);
+}
+
+#[test]
+fn run_errors() {
+    let program = "fn f(s stream<u8>) -> (out u8) { s.next(); return s.next() + 1; }";
+    let err = run(program, "f", vec![Value::bytes(b"a")]).unwrap_err();
+    assert_eq!(err.to_string(), "cannot apply `+` to none and 1");
+    assert_eq!(err.span(), 50..62);
+
+    let program = "fn f() { x := SOURCES::stdin; }";
+    let err = run(program, "f", vec![]).unwrap_err();
+    assert_eq!(err.to_string(), "expected a value, found `SOURCES::stdin`");
+
+    let program = "fn f() -> (out i32) { return 1 / 0; }";
+    let err = run(program, "f", vec![]).unwrap_err();
+    assert_eq!(err.to_string(), "attempt to divide by zero");
+
+    let program = "fn f() -> (out i32) { return 3!; }";
+    let err = run(program, "f", vec![]).unwrap_err();
+    assert_eq!(err.to_string(), "postfix `!` is not supported");
+}
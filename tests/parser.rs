@@ -1,12 +1,14 @@
 use burn::lexer::Token;
-use burn::parser::ast::{Expr, Item, Lit, Stmt, Type};
-use burn::parser::Parser;
+use burn::parser::ast::{
+    Attribute, Expr, ExprKind, IntSuffix, Item, ItemKind, Lit, Name, Param, Stmt, StmtKind, Type,
+};
+use burn::parser::{ParseError, Parser};
 
 #[test]
 fn parse_literals() {
     fn parse(input: &str) -> Expr {
         let mut parser = Parser::new(input);
-        parser.expression()
+        parser.expression().unwrap()
     }
     assert_eq!(parse("1  ").to_string(), "1");
     assert_eq!(parse("  \"string\"").to_string(), "\"string\"");
@@ -16,11 +18,134 @@ fn parse_literals() {
     );
 }
 
+#[test]
+fn parse_escapes() {
+    fn parse(input: &str) -> Lit {
+        let mut parser = Parser::new(input);
+        match parser.expression().unwrap().kind {
+            ExprKind::Literal(lit) => lit,
+            _ => unreachable!(),
+        }
+    }
+    assert_eq!(
+        parse(r"'Hello, world!\n'"),
+        Lit::Str(b"Hello, world!\n".to_vec())
+    );
+    assert_eq!(
+        parse(r#""\r\t\0\\\'\"\`\x7f\xFF""#),
+        Lit::Str(b"\r\t\0\\'\"`\x7f\xff".to_vec())
+    );
+    assert_eq!(parse(r"'it\'s'"), Lit::Str(b"it's".to_vec()));
+    assert_eq!(parse(r#""say \"hi\"""#), Lit::Str(b"say \"hi\"".to_vec()));
+    assert_eq!(parse(r"'\\'"), Lit::Str(b"\\".to_vec()));
+    assert_eq!(parse("`a`"), Lit::Byt(b'a'));
+    assert_eq!(parse(r"`\n`"), Lit::Byt(b'\n'));
+    assert_eq!(parse(r"`\``"), Lit::Byt(b'`'));
+    assert_eq!(parse(r"`\\`"), Lit::Byt(b'\\'));
+    assert_eq!(parse(r"`\x00`"), Lit::Byt(0));
+
+    fn parse_err(input: &str) -> ParseError {
+        let mut parser = Parser::new(input);
+        parser.expression().unwrap_err().remove(0)
+    }
+    let err = parse_err(r"'foo\qbar'");
+    assert_eq!(err.to_string(), "unknown escape sequence `\\q`");
+    assert_eq!(err.found, Token::String);
+    assert_eq!(err.span, 4..6);
+    let err = parse_err(r"'\xg0'");
+    assert_eq!(err.to_string(), "expected two hex digits after `\\x`");
+    assert_eq!(err.span, 1..3);
+    let err = parse_err("`ab`");
+    assert_eq!(
+        err.to_string(),
+        "byte literal must contain exactly one byte, found 2"
+    );
+    assert_eq!(err.found, Token::Byte);
+    assert_eq!(err.span, 0..4);
+    assert!(parse_err("``").to_string().ends_with("found 0"));
+    assert!(parse_err("`é`").to_string().ends_with("found 2"));
+
+    // every invalid escape in a file is reported
+    let mut parser = Parser::new(r"fn main() { '\q' -> out; x := '\w\e'; }");
+    assert_eq!(parser.file().unwrap_err().len(), 3);
+}
+
+#[test]
+fn parse_bool_literals() {
+    fn parse(input: &str) -> Expr {
+        let mut parser = Parser::new(input);
+        parser.expression().unwrap()
+    }
+    assert_eq!(
+        parse("true"),
+        Expr::from(ExprKind::Literal(Lit::Bool(true)))
+    );
+    assert_eq!(
+        parse("false"),
+        Expr::from(ExprKind::Literal(Lit::Bool(false)))
+    );
+    assert_eq!(
+        parse("!false || x == true").to_string(),
+        "((!false) || (x == true))"
+    );
+
+    let mut parser = Parser::new("while true { break; }");
+    assert_eq!(
+        parser.statement().unwrap().to_string(),
+        "while true {\n    break;\n}"
+    );
+}
+
+#[test]
+fn parse_int_literals() {
+    fn parse(input: &str) -> Lit {
+        let mut parser = Parser::new(input);
+        match parser.expression().unwrap().kind {
+            ExprKind::Literal(lit) => lit,
+            _ => unreachable!(),
+        }
+    }
+    fn int(value: u64, suffix: Option<IntSuffix>) -> Lit {
+        Lit::Int { value, suffix }
+    }
+    assert_eq!(parse("0xFF"), int(255, None));
+    assert_eq!(parse("0o17"), int(15, None));
+    assert_eq!(parse("0b1010"), int(10, None));
+    assert_eq!(parse("1_000_000"), int(1_000_000, None));
+    assert_eq!(parse("255u8"), int(255, Some(IntSuffix::U8)));
+    assert_eq!(parse("0x7fi32"), int(127, Some(IntSuffix::I32)));
+
+    // the sign is a prefix operator, the suffix stays on the literal
+    let mut parser = Parser::new("-1i64");
+    assert_eq!(parser.expression().unwrap().to_string(), "(-1i64)");
+
+    fn parse_err(input: &str) -> ParseError {
+        let mut parser = Parser::new(input);
+        parser.expression().unwrap_err().remove(0)
+    }
+    let err = parse_err("x + 99999999999999999999");
+    assert_eq!(
+        err.to_string(),
+        "integer literal is too large, the maximum is 18446744073709551615"
+    );
+    assert_eq!(err.span, 4..24);
+    let err = parse_err("0b1012");
+    assert_eq!(err.to_string(), "invalid digit `2` in binary literal");
+    assert_eq!(err.span, 5..6);
+    let err = parse_err("12u128");
+    assert_eq!(err.to_string(), "invalid suffix `u128` for number literal");
+    assert_eq!(err.span, 2..6);
+    assert_eq!(parse_err("0x").to_string(), "missing digits after `0x`");
+}
+
 #[test]
 fn parse_idents() {
     fn test_parse(input: &str, expected: &str) {
         let mut parser = Parser::new(input);
-        assert_eq!(parser.expression(), Expr::Ident(expected.to_string()));
+        assert_eq!(
+            parser.expression().unwrap(),
+            Expr::from(ExprKind::Ident(expected.to_string()))
+        );
     }
     test_parse("foo", "foo");
     test_parse("   Bar", "Bar");
@@ -31,23 +156,23 @@ fn parse_idents() {
 fn parse_comparisons() {
     fn parse(input: &str) -> Expr {
         let mut parser = Parser::new(input);
-        parser.expression()
+        parser.expression().unwrap()
     }
     assert_eq!(
         parse("byte >= `A` && byte <= `Z`"),
-        Expr::InfixOp {
+        Expr::from(ExprKind::InfixOp {
             op: Token::And,
-            lhs: Box::new(Expr::InfixOp {
+            lhs: Box::new(Expr::from(ExprKind::InfixOp {
                 op: Token::GreaterOrEqual,
-                lhs: Box::new(Expr::Ident("byte".to_string())),
-                rhs: Box::new(Expr::Literal(Lit::Byt("A".to_string()))),
-            }),
-            rhs: Box::new(Expr::InfixOp {
+                lhs: Box::new(Expr::from(ExprKind::Ident("byte".to_string()))),
+                rhs: Box::new(Expr::from(ExprKind::Literal(Lit::Byt(b'A')))),
+            })),
+            rhs: Box::new(Expr::from(ExprKind::InfixOp {
                 op: Token::LessOrEqual,
-                lhs: Box::new(Expr::Ident("byte".to_string())),
-                rhs: Box::new(Expr::Literal(Lit::Byt("Z".to_string()))),
-            }),
-        }
+                lhs: Box::new(Expr::from(ExprKind::Ident("byte".to_string()))),
+                rhs: Box::new(Expr::from(ExprKind::Literal(Lit::Byt(b'Z')))),
+            })),
+        })
     );
 }
 
@@ -55,159 +180,288 @@ fn parse_comparisons() {
 fn parse_fn_calls() {
     fn parse(input: &str) -> Expr {
         let mut parser = Parser::new(input);
-        parser.expression()
+        parser.expression().unwrap()
     }
 
     assert_eq!(
         parse("foo(bar, 0)"),
-        Expr::FnCall {
+        Expr::from(ExprKind::FnCall {
             fn_name: "foo".to_string(),
-            args: vec![Expr::Ident("bar".to_string()), Expr::Literal(Lit::Int(0))],
-        }
+            args: vec![
+                Expr::from(ExprKind::Ident("bar".to_string())),
+                Expr::from(ExprKind::Literal(Lit::Int {
+                    value: 0,
+                    suffix: None
+                }))
+            ],
+        })
     );
 
     assert_eq!(
         parse("foo(bar(baz))"),
-        Expr::FnCall {
+        Expr::from(ExprKind::FnCall {
             fn_name: "foo".to_string(),
-            args: vec![Expr::FnCall {
+            args: vec![Expr::from(ExprKind::FnCall {
                 fn_name: "bar".to_string(),
-                args: vec![Expr::Ident("baz".to_string())],
-            }]
-        },
+                args: vec![Expr::from(ExprKind::Ident("baz".to_string()))],
+            })]
+        }),
     );
 
     assert_eq!(
         parse("foo( )"),
-        Expr::FnCall {
+        Expr::from(ExprKind::FnCall {
             fn_name: "foo".to_string(),
             args: vec![],
-        }
+        })
     );
 
     assert_eq!(
         parse("foo -> bar()"),
-        Expr::FnCall {
-            fn_name: "bar".to_string(),
-            args: vec![Expr::Ident("foo".to_string())],
-        }
+        Expr::from(ExprKind::Pipe {
+            value: Box::new(Expr::from(ExprKind::Ident("foo".to_string()))),
+            call: Box::new(Expr::from(ExprKind::FnCall {
+                fn_name: "bar".to_string(),
+                args: vec![],
+            })),
+        })
     );
 
     assert_eq!(
         parse("foo() -> bar(\"baz\")"),
-        Expr::FnCall {
-            fn_name: "bar".to_string(),
-            args: vec![
-                Expr::Literal(Lit::Str("baz".to_string())),
-                Expr::FnCall {
-                    fn_name: "foo".to_string(),
-                    args: vec![],
-                }
-            ],
-        }
+        Expr::from(ExprKind::Pipe {
+            value: Box::new(Expr::from(ExprKind::FnCall {
+                fn_name: "foo".to_string(),
+                args: vec![],
+            })),
+            call: Box::new(Expr::from(ExprKind::FnCall {
+                fn_name: "bar".to_string(),
+                args: vec![Expr::from(ExprKind::Literal(Lit::Str(b"baz".to_vec())))],
+            })),
+        })
     );
 
-    assert_eq!(parse("1+2*3 -> foo()").to_string(), "foo((1 + (2 * 3)))");
+    assert_eq!(
+        parse("1+2*3 -> foo()").to_string(),
+        "((1 + (2 * 3)) -> foo())"
+    );
     assert_eq!(
         parse("foo() -> bar() -> baz()").to_string(),
-        "baz(bar(foo()))"
+        "((foo() -> bar()) -> baz())"
+    );
+    assert_eq!(parse("foo::bar()").to_string(), "foo::bar()");
+    assert_eq!(
+        parse("1 -> foo::bar(_, 2)").to_string(),
+        "(1 -> foo::bar(_, 2))"
+    );
+    assert_eq!(
+        parse("('hello', 'world') -> mix(_, foo, _)").to_string(),
+        "((\"hello\", \"world\") -> mix(_, foo, _))"
+    );
+}
+
+#[test]
+fn parse_method_calls() {
+    let mut parser = Parser::new("foo.bar()");
+
+    assert_eq!(
+        parser.expression().unwrap(),
+        Expr::from(ExprKind::MethodCall {
+            receiver: Box::new(Expr::from(ExprKind::Ident("foo".to_string()))),
+            method: "bar".to_string(),
+            args: vec![],
+        })
     );
+
+    parser = Parser::new("foo.bar().baz(1)");
     assert_eq!(
-        parse("foo() -> bar() -> baz() -> buzz()").to_string(),
-        "buzz(baz(bar(foo())))"
+        parser.expression().unwrap(),
+        Expr::from(ExprKind::MethodCall {
+            receiver: Box::new(Expr::from(ExprKind::MethodCall {
+                receiver: Box::new(Expr::from(ExprKind::Ident("foo".to_string()))),
+                method: "bar".to_string(),
+                args: vec![],
+            })),
+            method: "baz".to_string(),
+            args: vec![Expr::from(ExprKind::Literal(Lit::Int {
+                value: 1,
+                suffix: None
+            }))],
+        }),
     );
-    assert_eq!(parse("foo::bar()").to_string(), "(foo :: bar())");
 
+    parser = Parser::new("1 -> foo::bar.baz()");
     assert_eq!(
-        parse("('hello', 'world') -> mix()").to_string(),
-        "mix(\"hello\", \"world\")"
+        parser.expression().unwrap(),
+        Expr::from(ExprKind::Pipe {
+            value: Box::new(Expr::from(ExprKind::Literal(Lit::Int {
+                value: 1,
+                suffix: None
+            }))),
+            call: Box::new(Expr::from(ExprKind::MethodCall {
+                receiver: Box::new(Expr::from(ExprKind::Path {
+                    segments: vec!["foo".to_string(), "bar".to_string()],
+                })),
+                method: "baz".to_string(),
+                args: vec![],
+            })),
+        }),
     );
+}
+
+#[test]
+fn parse_dot_operator() {
+    fn parse(input: &str) -> Expr {
+        let mut parser = Parser::new(input);
+        parser.expression().unwrap()
+    }
+    assert_eq!(parse("foo.bar()").to_string(), "foo.bar()");
+    assert_eq!(parse("foo.bar().baz()").to_string(), "foo.bar().baz()");
+    assert_eq!(parse("1.ascii()").to_string(), "1.ascii()");
+    assert_eq!(parse("foo.bar() + baz").to_string(), "(foo.bar() + baz)");
     assert_eq!(
-        parse("'world' -> mix('hello', _)").to_string(),
-        "mix(\"hello\", \"world\")"
+        parse("1 + foo.bar() + baz").to_string(),
+        "((1 + foo.bar()) + baz)"
     );
     assert_eq!(
-        parse("'hello' -> mix(_, 'world')").to_string(),
-        "mix(\"hello\", \"world\")"
+        parse("1 + foo.bar() * 2").to_string(),
+        "(1 + (foo.bar() * 2))"
     );
+    assert_eq!(parse("(a + b).len()").to_string(), "(a + b).len()");
+
+    // `.` is always followed by a method call
+    let mut parser = Parser::new("foo.bar");
     assert_eq!(
-        parse("('hello', 'world') -> mix(_, foo, _)").to_string(),
-        "mix(\"hello\", foo, \"world\")"
+        parser.expression().unwrap_err().remove(0).to_string(),
+        "expected `(`, found `<EOF>`"
     );
+    let mut parser = Parser::new("foo.1()");
     assert_eq!(
-        parse("('hello', 'world', bar) -> mix(_, foo, _)").to_string(),
-        "mix(\"hello\", foo, \"world\", bar)"
+        parser.expression().unwrap_err().remove(0).to_string(),
+        "expected `ident`, found `number`"
     );
 }
 
 #[test]
-fn parse_method_calls() {
-    let mut parser = Parser::new("foo.bar()");
+fn parse_paths() {
+    fn parse(input: &str) -> Expr {
+        let mut parser = Parser::new(input);
+        parser.expression().unwrap()
+    }
+    assert_eq!(
+        parse("a::b::c"),
+        Expr::from(ExprKind::Path {
+            segments: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+        })
+    );
+    assert_eq!(
+        parse("a::b::c(x)"),
+        Expr::from(ExprKind::PathCall {
+            path: Box::new(Expr::from(ExprKind::Path {
+                segments: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            })),
+            args: vec![Expr::from(ExprKind::Ident("x".to_string()))],
+        })
+    );
+    assert_eq!(parse("a::b::c").to_string(), "a::b::c");
+    match parse("a::b(1)").kind {
+        ExprKind::PathCall { path, .. } => assert_eq!(path.span, 0..4),
+        _ => unreachable!(),
+    }
+    assert_eq!(
+        parse("SOURCES::stdin().len()").to_string(),
+        "SOURCES::stdin().len()"
+    );
+    assert_eq!(parse("a::b == c").to_string(), "(a::b == c)");
 
+    let mut parser = Parser::new("  foo()::bar");
+    let err = parser.expression().unwrap_err().remove(0);
+    assert_eq!(err.to_string(), "expected a namespace before `::`");
+    assert_eq!(err.span, 2..7);
+    let mut parser = Parser::new("a::");
     assert_eq!(
-        parser.expression(),
-        Expr::InfixOp {
-            op: Token::Dot,
-            lhs: Box::new(Expr::Ident("foo".to_string())),
-            rhs: Box::new(Expr::FnCall {
-                fn_name: "bar".to_string(),
-                args: vec![],
-            }),
-        }
+        parser.expression().unwrap_err().remove(0).to_string(),
+        "expected `ident`, found `<EOF>`"
     );
+}
 
-    parser = Parser::new("1 -> foo.bar.baz()");
+#[test]
+fn parse_index() {
+    fn parse(input: &str) -> Expr {
+        let mut parser = Parser::new(input);
+        parser.expression().unwrap()
+    }
     assert_eq!(
-        parser.expression(),
-        Expr::InfixOp {
-            op: Token::Dot,
-            lhs: Box::new(Expr::Ident("foo".to_string())),
-            rhs: Box::new(Expr::InfixOp {
-                op: Token::Dot,
-                lhs: Box::new(Expr::Ident("bar".to_string())),
-                rhs: Box::new(Expr::FnCall {
-                    fn_name: "baz".to_string(),
-                    args: vec![Expr::Literal(Lit::Int(1))],
-                }),
-            }),
-        },
+        parse("s[0]"),
+        Expr::from(ExprKind::Index {
+            expr: Box::new(Expr::from(ExprKind::Ident("s".to_string()))),
+            index: Box::new(Expr::from(ExprKind::Literal(Lit::Int {
+                value: 0,
+                suffix: None
+            }))),
+        })
+    );
+    assert_eq!(
+        parse("s[i..]"),
+        Expr::from(ExprKind::Index {
+            expr: Box::new(Expr::from(ExprKind::Ident("s".to_string()))),
+            index: Box::new(Expr::from(ExprKind::Range {
+                start: Some(Box::new(Expr::from(ExprKind::Ident("i".to_string())))),
+                end: None,
+            })),
+        })
+    );
+    assert_eq!(parse("s[1..n + 1]").to_string(), "(s[1..(n + 1)])");
+    assert_eq!(parse("s[..]").to_string(), "(s[..])");
+    assert_eq!(parse("s[..2][0]").to_string(), "((s[..2])[0])");
+    assert_eq!(parse("!s[0]").to_string(), "(!(s[0]))");
+    assert_eq!(parse("s[0] + 1").to_string(), "((s[0]) + 1)");
+    assert_eq!(
+        parse("words.next()[1..]").to_string(),
+        "(words.next()[1..])"
     );
+    assert_eq!(parse("s[0] -> f()").to_string(), "((s[0]) -> f())");
 
-    parser = Parser::new("1 -> foo::bar.baz()");
+    let span = parse("  s[1..2]").span;
+    assert_eq!(span, 2..9);
+
+    let mut parser = Parser::new("s[1");
     assert_eq!(
-        parser.expression(),
-        Expr::InfixOp {
-            op: Token::DoubleColon,
-            lhs: Box::new(Expr::Ident("foo".to_string())),
-            rhs: Box::new(Expr::InfixOp {
-                op: Token::Dot,
-                lhs: Box::new(Expr::Ident("bar".to_string())),
-                rhs: Box::new(Expr::FnCall {
-                    fn_name: "baz".to_string(),
-                    args: vec![Expr::Literal(Lit::Int(1))],
-                }),
-            }),
-        },
+        parser.expression().unwrap_err().remove(0).to_string(),
+        "expected `]`, found `<EOF>`"
     );
+    let mut parser = Parser::new("s[1...2]");
+    assert!(parser.expression().is_err());
 }
 
 #[test]
-fn parse_dot_operator() {
+fn parse_options() {
     fn parse(input: &str) -> Expr {
         let mut parser = Parser::new(input);
-        parser.expression()
+        parser.expression().unwrap()
     }
-    assert_eq!(parse("foo.bar").to_string(), "(foo . bar)");
-    assert_eq!(parse("foo.bar.baz").to_string(), "(foo . (bar . baz))");
-    assert_eq!(parse("1.2").to_string(), "(1 . 2)");
-    assert_eq!(parse("foo.bar + baz").to_string(), "((foo . bar) + baz)");
+    assert_eq!(parse("none"), Expr::from(ExprKind::Literal(Lit::None)));
+    assert_eq!(
+        parse("s.next()?"),
+        Expr::from(ExprKind::PostfixOp {
+            op: Token::Question,
+            expr: Box::new(parse("s.next()")),
+        })
+    );
+    assert_eq!(parse("s.next()? + 1").to_string(), "((s.next()?) + 1)");
+    assert_eq!(parse("!s.get(0)?").to_string(), "(!(s.get(0)?))");
+    assert_eq!(parse("s[0]?").to_string(), "((s[0])?)");
+    assert_eq!(parse("s.next()?.len()").to_string(), "(s.next()?).len()");
+    assert_eq!(parse("x == none").to_string(), "(x == none)");
     assert_eq!(
-        parse("1 + foo.bar + baz").to_string(),
-        "((1 + (foo . bar)) + baz)"
+        parse("s.next()? -> f()").to_string(),
+        "((s.next()?) -> f())"
     );
+
+    let mut parser = Parser::new("?x");
     assert_eq!(
-        parse("1 + foo.bar * 2").to_string(),
-        "(1 + ((foo . bar) * 2))"
+        parser.expression().unwrap_err().remove(0).to_string(),
+        "expected expression, found `?`"
     );
 }
 
@@ -215,7 +469,7 @@ fn parse_dot_operator() {
 fn parse_arithmetic() {
     fn parse(input: &str) -> Expr {
         let mut parser = Parser::new(input);
-        parser.expression()
+        parser.expression().unwrap()
     }
     assert_eq!(parse("1 + 2").to_string(), "(1 + 2)");
     assert_eq!(parse("1 + 2 + 3").to_string(), "((1 + 2) + 3)");
@@ -232,7 +486,7 @@ fn parse_arithmetic() {
 fn parse_binary_expressions() {
     fn parse(input: &str) -> Expr {
         let mut parser = Parser::new(input);
-        parser.expression()
+        parser.expression().unwrap()
     }
 
     assert_eq!(
@@ -241,18 +495,47 @@ fn parse_binary_expressions() {
     );
     assert_eq!(parse("1 + 2 == 3 + 4").to_string(), "((1 + 2) == (3 + 4))");
     assert_eq!(parse("1 < 2 == 3 > 4").to_string(), "((1 < 2) == (3 > 4))");
-    assert_eq!(parse("1 -> foo() < -10").to_string(), "(foo(1) < (-10))");
+    assert_eq!(
+        parse("1 -> foo() < -10").to_string(),
+        "((1 -> foo()) < (-10))"
+    );
     assert_eq!(
         parse("1 -> foo() == 2 -> bar()").to_string(),
-        "(foo(1) == bar(2))"
+        "((1 -> foo()) == (2 -> bar()))"
+    );
+}
+
+#[test]
+fn parse_bitwise_expressions() {
+    fn parse(input: &str) -> Expr {
+        let mut parser = Parser::new(input);
+        parser.expression().unwrap()
+    }
+    assert_eq!(parse("7 % 3").to_string(), "(7 % 3)");
+    assert_eq!(parse("1 + 7 % 3 * 2").to_string(), "(1 + ((7 % 3) * 2))");
+    assert_eq!(parse("1 << 2 + 3").to_string(), "(1 << (2 + 3))");
+    assert_eq!(parse("x >> 4 << 1").to_string(), "((x >> 4) << 1)");
+    assert_eq!(parse("x >> 1 < y").to_string(), "((x >> 1) < y)");
+    assert_eq!(parse("a | b ^ c & d").to_string(), "(a | (b ^ (c & d)))");
+    assert_eq!(parse("a & b == c").to_string(), "(a & (b == c))");
+    assert_eq!(
+        parse("a | b && c ^ d || e").to_string(),
+        "(((a | b) && (c ^ d)) || e)"
     );
+    assert_eq!(parse("~x & 0xFF").to_string(), "((~x) & 255)");
+    assert_eq!(parse("~~x").to_string(), "(~(~x))");
+    assert_eq!(parse("x << 1 -> f()").to_string(), "((x << 1) -> f())");
+
+    // member access binds tighter than prefix operators
+    assert_eq!(parse("!s.eof()").to_string(), "(!s.eof())");
+    assert_eq!(parse("-a.b()").to_string(), "(-a.b())");
 }
 
 #[test]
 fn parse_statements() {
     fn parse(input: &str) -> Stmt {
         let mut parser = Parser::new(input);
-        parser.statement()
+        parser.statement().unwrap()
     }
 
     let stmt = parse(
@@ -275,40 +558,44 @@ fn parse_statements() {
     "#,
     );
 
-    let stmts = match stmt {
-        Stmt::Block { stmts } => stmts,
+    let stmts = match stmt.kind {
+        StmtKind::Block { stmts } => stmts,
         _ => unreachable!(),
     };
     assert_eq!(stmts.len(), 2);
 
     let let_stmt = &stmts[0];
-    match let_stmt {
-        Stmt::Declaration { var_names, .. } => assert_eq!(var_names, &vec!["x".to_string()]),
+    match &let_stmt.kind {
+        StmtKind::Declaration { var_names, .. } => {
+            assert_eq!(var_names, &vec![Name::new("x")])
+        }
         _ => unreachable!(),
     }
 
-    let stmts = match &stmts[1] {
-        Stmt::Block { stmts } => stmts,
+    let stmts = match &stmts[1].kind {
+        StmtKind::Block { stmts } => stmts,
         _ => unreachable!(),
     };
     assert_eq!(stmts.len(), 2);
 
     let assignment_stmt = &stmts[0];
-    match assignment_stmt {
-        Stmt::Assignment { var_names, .. } => assert_eq!(var_names, &vec!["x".to_string()]),
+    match &assignment_stmt.kind {
+        StmtKind::Assignment { var_names, .. } => {
+            assert_eq!(var_names, &vec![Name::new("x")])
+        }
         _ => unreachable!(),
     }
 
     let if_stmt = &stmts[1];
-    match if_stmt {
-        Stmt::IfStmt {
+    match &if_stmt.kind {
+        StmtKind::IfStmt {
             condition,
             body,
             else_stmt,
         } => {
             assert!(matches!(
-                condition,
-                Expr::InfixOp {
+                &condition.kind,
+                ExprKind::InfixOp {
                     op: Token::LeftAngleBracket,
                     lhs: _lhs,
                     rhs: _rhs,
@@ -316,13 +603,17 @@ fn parse_statements() {
             ));
             assert_eq!(body.len(), 2);
             let x_assignment = &body[0];
-            match x_assignment {
-                Stmt::Assignment { var_names, .. } => assert_eq!(var_names, &vec!["x".to_string()]),
+            match &x_assignment.kind {
+                StmtKind::Assignment { var_names, .. } => {
+                    assert_eq!(var_names, &vec![Name::new("x")])
+                }
                 _ => unreachable!(),
             }
             let y_assignment = &body[1];
-            match y_assignment {
-                Stmt::Assignment { var_names, .. } => assert_eq!(var_names, &vec!["y".to_string()]),
+            match &y_assignment.kind {
+                StmtKind::Assignment { var_names, .. } => {
+                    assert_eq!(var_names, &vec![Name::new("y")])
+                }
                 _ => unreachable!(),
             }
 
@@ -331,15 +622,15 @@ fn parse_statements() {
                 None => unreachable!(),
             };
 
-            match else_stmt {
-                Stmt::IfStmt {
+            match &else_stmt.kind {
+                StmtKind::IfStmt {
                     condition,
                     body,
                     else_stmt,
                 } => {
                     assert!(matches!(
-                        condition,
-                        Expr::InfixOp {
+                        &condition.kind,
+                        ExprKind::InfixOp {
                             op: Token::LeftAngleBracket,
                             lhs: _lhs,
                             rhs: _rhs,
@@ -347,16 +638,16 @@ fn parse_statements() {
                     ));
                     assert_eq!(body.len(), 2);
                     let let_i = &body[0];
-                    match let_i {
-                        Stmt::Declaration { var_names, .. } => {
-                            assert_eq!(var_names, &vec!["i".to_string()])
+                    match &let_i.kind {
+                        StmtKind::Declaration { var_names, .. } => {
+                            assert_eq!(var_names, &vec![Name::new("i")])
                         }
                         _ => unreachable!(),
                     }
                     let x_assignment = &body[1];
-                    match x_assignment {
-                        Stmt::Assignment { var_names, .. } => {
-                            assert_eq!(var_names, &vec!["x".to_string()])
+                    match &x_assignment.kind {
+                        StmtKind::Assignment { var_names, .. } => {
+                            assert_eq!(var_names, &vec![Name::new("x")])
                         }
                         _ => unreachable!(),
                     }
@@ -366,16 +657,16 @@ fn parse_statements() {
                         None => unreachable!(),
                     };
 
-                    let stmts = match else_stmt {
-                        Stmt::Block { stmts } => stmts,
+                    let stmts = match &else_stmt.kind {
+                        StmtKind::Block { stmts } => stmts,
                         _ => unreachable!(),
                     };
                     assert_eq!(stmts.len(), 1);
 
                     let x_assignment = &stmts[0];
-                    match x_assignment {
-                        Stmt::Assignment { var_names, .. } => {
-                            assert_eq!(var_names, &vec!["x".to_string()])
+                    match &x_assignment.kind {
+                        StmtKind::Assignment { var_names, .. } => {
+                            assert_eq!(var_names, &vec![Name::new("x")])
                         }
                         _ => unreachable!(),
                     }
@@ -388,59 +679,306 @@ fn parse_statements() {
 
     assert_eq!(
         parse("foo::bar();"),
-        Stmt::Expr(Expr::InfixOp {
-            op: Token::DoubleColon,
-            lhs: Box::new(Expr::Ident("foo".to_string())),
-            rhs: Box::new(Expr::FnCall {
-                fn_name: "bar".to_string(),
+        Stmt::from(StmtKind::Expr(Expr::from(ExprKind::PathCall {
+            path: Box::new(Expr::from(ExprKind::Path {
+                segments: vec!["foo".to_string(), "bar".to_string()],
+            })),
+            args: vec![],
+        })))
+    );
+
+    // whether a call is a sink is left to the checker
+    assert_eq!(
+        parse("foo -> SINKS::stdout();"),
+        Stmt::from(StmtKind::Expr(Expr::from(ExprKind::Pipe {
+            value: Box::new(Expr::from(ExprKind::Ident("foo".to_string()))),
+            call: Box::new(Expr::from(ExprKind::PathCall {
+                path: Box::new(Expr::from(ExprKind::Path {
+                    segments: vec!["SINKS".to_string(), "stdout".to_string()],
+                })),
                 args: vec![],
+            })),
+        })))
+    );
+    assert_eq!(
+        parse("foo -> bar;"),
+        Stmt::from(StmtKind::StreamWrite {
+            value: Expr::from(ExprKind::Ident("foo".to_string())),
+            stream: Expr::from(ExprKind::Ident("bar".to_string())),
+        })
+    );
+    assert_eq!(
+        parse("foo -> bar() -> baz;"),
+        Stmt::from(StmtKind::StreamWrite {
+            value: Expr::from(ExprKind::Pipe {
+                value: Box::new(Expr::from(ExprKind::Ident("foo".to_string()))),
+                call: Box::new(Expr::from(ExprKind::FnCall {
+                    fn_name: "bar".to_string(),
+                    args: vec![],
+                })),
             }),
+            stream: Expr::from(ExprKind::Ident("baz".to_string())),
         })
     );
+    assert_eq!(parse("x -> f();").to_string(), "(x -> f());");
+    assert_eq!(parse("x << 1 -> out;").to_string(), "(x << 1) -> out;");
 
+    // a write is only allowed as a statement of its own
+    let err = Parser::new("foo -> bar -> baz;")
+        .statement()
+        .unwrap_err()
+        .remove(0);
+    assert_eq!(err.to_string(), "expected `;`, found `->`");
+    let err = Parser::new("x := foo -> bar;")
+        .statement()
+        .unwrap_err()
+        .remove(0);
     assert_eq!(
-        parse("foo -> bar;"),
-        Stmt::Expr(Expr::InfixOp {
-            op: Token::RightArrow,
-            lhs: Box::new(Expr::Ident("foo".to_string())),
-            rhs: Box::new(Expr::Ident("bar".to_string())),
-        })
+        err.to_string(),
+        "a write to a stream must be a statement of its own"
     );
+    assert_eq!(err.span, 5..15);
+    let err = Parser::new("f(a -> s);").statement().unwrap_err().remove(0);
+    assert_eq!(err.span, 2..8);
+    let err = Parser::new("a -> s == b;")
+        .statement()
+        .unwrap_err()
+        .remove(0);
+    assert_eq!(err.to_string(), "expected `;`, found `==`");
+}
 
+#[test]
+fn parse_fan_out() {
+    fn parse(input: &str) -> Stmt {
+        let mut parser = Parser::new(input);
+        parser.statement().unwrap()
+    }
     assert_eq!(
-        parse("foo -> bar -> baz;"),
-        // should be ((foo -> bar) -> baz)
-        Stmt::Expr(Expr::InfixOp {
-            op: Token::RightArrow,
-            lhs: Box::new(Expr::InfixOp {
-                op: Token::RightArrow,
-                lhs: Box::new(Expr::Ident("foo".to_string())),
-                rhs: Box::new(Expr::Ident("bar".to_string())),
+        parse("dup(x) -> (a, b);"),
+        Stmt::from(StmtKind::StreamWrite {
+            value: Expr::from(ExprKind::FnCall {
+                fn_name: "dup".to_string(),
+                args: vec![Expr::from(ExprKind::Ident("x".to_string()))],
             }),
-            rhs: Box::new(Expr::Ident("baz".to_string())),
+            stream: Expr::from(ExprKind::Tuple(vec![
+                Expr::from(ExprKind::Ident("a".to_string())),
+                Expr::from(ExprKind::Ident("b".to_string())),
+            ])),
         })
     );
+    assert_eq!(
+        parse("dup(SOURCES::stdin()) -> (SINKS::stdout(), SINKS::stderr());").to_string(),
+        "dup(SOURCES::stdin()) -> (SINKS::stdout(), SINKS::stderr());"
+    );
+    assert_eq!(
+        parse("x -> dup() -> (a, b);").to_string(),
+        "(x -> dup()) -> (a, b);"
+    );
+    // a single stream in parentheses is a plain write
+    assert_eq!(parse("x -> (a);").to_string(), "x -> a;");
+
+    fn error(input: &str) -> ParseError {
+        let mut parser = Parser::new(input);
+        parser.statement().unwrap_err().remove(0)
+    }
+    let err = error("x -> (a, 1);");
+    assert_eq!(err.to_string(), "expected a stream, found `1`");
+    assert_eq!(err.span, 9..10);
+    let err = error("x -> (a, b + c);");
+    assert_eq!(err.to_string(), "expected a stream, found `(b + c)`");
+    let err = error("x -> (a, b -> c);");
+    assert_eq!(
+        err.to_string(),
+        "a write to a stream must be a statement of its own"
+    );
+    let err = error("x -> 1;");
+    assert_eq!(
+        err.to_string(),
+        "expected a function call, a stream or a tuple of streams after `->`, found `number`"
+    );
+    assert_eq!(err.span, 5..6);
+    let err = error("x -> -a;");
+    assert_eq!(err.span, 5..7);
+    let err = error("x -> 'a';");
+    assert_eq!(err.found, Token::String);
+    assert!(Parser::new("x := y -> (a, b);").statement().is_err());
 
     assert_eq!(
         parse("x, y := foo();"),
-        Stmt::Declaration {
-            var_names: vec!["x".to_string(), "y".to_string()],
-            value: Expr::FnCall {
+        Stmt::from(StmtKind::Declaration {
+            var_names: vec![Name::new("x"), Name::new("y")],
+            value: Expr::from(ExprKind::FnCall {
                 fn_name: "foo".to_string(),
                 args: vec![],
-            },
-        },
+            }),
+        }),
     );
 
     assert_eq!(
         parse("x, y = foo();"),
-        Stmt::Assignment {
-            var_names: vec!["x".to_string(), "y".to_string()],
-            value: Expr::FnCall {
+        Stmt::from(StmtKind::Assignment {
+            var_names: vec![Name::new("x"), Name::new("y")],
+            value: Expr::from(ExprKind::FnCall {
                 fn_name: "foo".to_string(),
                 args: vec![],
-            },
-        },
+            }),
+        }),
+    );
+}
+
+#[test]
+fn parse_discards() {
+    fn parse(input: &str) -> Stmt {
+        let mut parser = Parser::new(input);
+        parser.statement().unwrap()
+    }
+    assert_eq!(
+        parse("x, _ := f();"),
+        Stmt::from(StmtKind::Declaration {
+            var_names: vec![Name::new("x"), Name::new("_")],
+            value: Expr::from(ExprKind::FnCall {
+                fn_name: "f".to_string(),
+                args: vec![],
+            }),
+        })
+    );
+    assert_eq!(parse("_, y = f();").to_string(), "_, y = f();");
+    assert_eq!(parse("_ := f();").to_string(), "_ := f();");
+    // `_` on its own is still an expression
+    assert_eq!(parse("_ -> out;").to_string(), "_ -> out;");
+    let err = Parser::new("_ += 1;").statement().unwrap_err().remove(0);
+    assert_eq!(err.to_string(), "expected `;`, found `+=`");
+    let err = Parser::new("x, 1 := f();")
+        .statement()
+        .unwrap_err()
+        .remove(0);
+    assert_eq!(
+        err.to_string(),
+        "expected one of `ident`, `_`, found `number`"
+    );
+}
+
+#[test]
+fn parse_compound_assignments() {
+    fn parse(input: &str) -> Stmt {
+        let mut parser = Parser::new(input);
+        parser.statement().unwrap()
+    }
+    assert_eq!(
+        parse("x += 1 + y;"),
+        Stmt::from(StmtKind::CompoundAssignment {
+            var_name: Name::new("x"),
+            op: Token::Plus,
+            value: Expr::from(ExprKind::InfixOp {
+                op: Token::Plus,
+                lhs: Box::new(Expr::from(ExprKind::Literal(Lit::Int {
+                    value: 1,
+                    suffix: None
+                }))),
+                rhs: Box::new(Expr::from(ExprKind::Ident("y".to_string()))),
+            }),
+        })
+    );
+    for op in &["+", "-", "*", "/", "%", "&", "|", "^", "<<", ">>"] {
+        let input = format!("x {}= 2;", op);
+        assert_eq!(parse(&input).to_string(), input);
+    }
+    assert_eq!(
+        parse("count++;"),
+        Stmt::from(StmtKind::Increment {
+            var_name: Name::new("count")
+        })
+    );
+    assert_eq!(
+        parse("count--;"),
+        Stmt::from(StmtKind::Decrement {
+            var_name: Name::new("count")
+        })
+    );
+
+    let stmt = parse(
+        r#"
+        for b in input {
+            total += b;
+            n++;
+        }
+    "#,
+    );
+    match &stmt.kind {
+        StmtKind::ForLoop { stmts, .. } => {
+            assert_eq!(stmts[0].to_string(), "total += b;");
+            assert_eq!(stmts[1].to_string(), "n++;");
+        }
+        _ => unreachable!(),
+    }
+
+    // increments are statements, not expressions
+    let mut parser = Parser::new("x := y++;");
+    assert_eq!(
+        parser.statement().unwrap_err().remove(0).to_string(),
+        "expected `;`, found `++`"
+    );
+}
+
+#[test]
+fn parse_returns() {
+    fn parse(input: &str) -> Stmt {
+        let mut parser = Parser::new(input);
+        parser.statement().unwrap()
+    }
+    assert_eq!(
+        parse("return;"),
+        Stmt::from(StmtKind::ReturnStmt { values: vec![] })
+    );
+    assert_eq!(
+        parse("return false, _, x + 1;"),
+        Stmt::from(StmtKind::ReturnStmt {
+            values: vec![
+                Expr::from(ExprKind::Literal(Lit::Bool(false))),
+                Expr::from(ExprKind::Placeholder),
+                Expr::from(ExprKind::InfixOp {
+                    op: Token::Plus,
+                    lhs: Box::new(Expr::from(ExprKind::Ident("x".to_string()))),
+                    rhs: Box::new(Expr::from(ExprKind::Literal(Lit::Int {
+                        value: 1,
+                        suffix: None
+                    }))),
+                }),
+            ],
+        })
+    );
+    assert_eq!(
+        parse("return false, 'HTTP/1.0 501 Unsupported Method\\r\\n';").to_string(),
+        "return false, \"HTTP/1.0 501 Unsupported Method\\r\\n\";"
+    );
+    let mut parser = Parser::new("return a,;");
+    assert_eq!(
+        parser.statement().unwrap_err().remove(0).to_string(),
+        "expected expression, found `;`"
+    );
+}
+
+#[test]
+fn parse_asserts() {
+    fn parse(input: &str) -> Stmt {
+        let mut parser = Parser::new(input);
+        parser.statement().unwrap()
+    }
+    assert_eq!(
+        parse("assert done;"),
+        Stmt::from(StmtKind::Assert {
+            condition: Expr::from(ExprKind::Ident("done".to_string())),
+            message: None,
+        })
+    );
+    assert_eq!(
+        parse("assert rot13('foo') == 'sbb', 'rotates';").to_string(),
+        "assert (rot13(\"foo\") == \"sbb\"), \"rotates\";"
+    );
+    let mut parser = Parser::new("assert;");
+    assert_eq!(
+        parser.statement().unwrap_err().remove(0).to_string(),
+        "expected expression, found `;`"
     );
 }
 
@@ -448,57 +986,37 @@ fn parse_statements() {
 fn parse_types() {
     fn parse(input: &str) -> Item {
         let mut parser = Parser::new(input);
-        parser.item()
+        parser.item().unwrap()
     }
     let func = parse("fn foo(a int, b stream<u8>, c stream<stream<u8>>) {}");
-    match func {
-        Item::Function {
+    match func.kind {
+        ItemKind::Function {
             name: _,
             parameters,
             body: _,
             return_params: _,
         } => {
             assert_eq!(parameters.len(), 3);
-            assert_eq!(
-                parameters[0],
-                (
-                    "a".to_string(),
-                    Type {
-                        name: "int".to_string(),
-                        generics: vec![],
-                    }
-                )
-            );
+            assert_eq!(parameters[0], Param::new("a", Type::new("int", vec![])));
             assert_eq!(
                 parameters[1],
-                (
-                    "b".to_string(),
-                    Type {
-                        name: "stream".to_string(),
-                        generics: vec![Type {
-                            name: "u8".to_string(),
-                            generics: vec![],
-                        }],
-                    }
-                )
+                Param::new("b", Type::new("stream", vec![Type::new("u8", vec![])]))
             );
             assert_eq!(
                 parameters[2],
-                (
-                    "c".to_string(),
-                    Type {
-                        name: "stream".to_string(),
-                        generics: vec![Type {
-                            name: "stream".to_string(),
-                            generics: vec![Type {
-                                name: "u8".to_string(),
-                                generics: vec![],
-                            }],
-                        }],
-                    }
+                Param::new(
+                    "c",
+                    Type::new(
+                        "stream",
+                        vec![Type::new("stream", vec![Type::new("u8", vec![])])]
+                    )
                 )
             );
+            // `>>` is split between the two generic lists
+            assert_eq!(parameters[2].typ.span, 30..48);
+            assert_eq!(parameters[2].typ.generics[0].span, 37..47);
         }
+        _ => unreachable!(),
     }
 }
 
@@ -506,7 +1024,7 @@ fn parse_types() {
 fn parse_fns() {
     fn parse(input: &str) -> Item {
         let mut parser = Parser::new(input);
-        parser.item()
+        parser.item().unwrap()
     }
 
     let func = parse(
@@ -518,8 +1036,8 @@ fn parse_fns() {
     "#,
     );
 
-    match func {
-        Item::Function {
+    match func.kind {
+        ItemKind::Function {
             name,
             parameters,
             body,
@@ -527,77 +1045,87 @@ fn parse_fns() {
         } => {
             assert_eq!(name, "foo");
             assert_eq!(parameters.len(), 2);
-            assert_eq!(
-                parameters[0],
-                (
-                    "a".to_string(),
-                    Type {
-                        name: "int".to_string(),
-                        generics: vec![],
-                    }
-                )
-            );
+            assert_eq!(parameters[0], Param::new("a", Type::new("int", vec![])));
             assert_eq!(
                 parameters[1],
-                (
-                    "b".to_string(),
-                    Type {
-                        name: "stream".to_string(),
-                        generics: vec![Type {
-                            name: "u8".to_string(),
-                            generics: vec![],
-                        }],
-                    }
-                )
+                Param::new("b", Type::new("stream", vec![Type::new("u8", vec![])]))
             );
             assert_eq!(
                 return_params,
-                vec![(
-                    "out".to_string(),
-                    Type {
-                        name: "stream".to_string(),
-                        generics: vec![Type {
-                            name: "u8".to_string(),
-                            generics: vec![],
-                        }],
-                    }
+                vec![Param::new(
+                    "out",
+                    Type::new("stream", vec![Type::new("u8", vec![])])
                 )]
             );
             assert_eq!(body.len(), 2);
             assert_eq!(
                 body[1],
-                Stmt::ReturnStmt {
-                    value: Some(Expr::Ident("b".to_string())),
-                }
+                Stmt::from(StmtKind::ReturnStmt {
+                    values: vec![Expr::from(ExprKind::Ident("b".to_string()))],
+                })
             );
         }
+        _ => unreachable!(),
+    }
+
+    // `->` before the outputs is optional, and always shown
+    let with_arrow = parse("fn dup(a stream<u8>) -> (b stream<u8>, c stream<u8>) {}");
+    let without_arrow = parse("fn dup(a stream<u8>) (b stream<u8>, c stream<u8>) {}");
+    assert_eq!(with_arrow, without_arrow);
+    assert_eq!(
+        without_arrow.to_string(),
+        "fn dup(a stream<u8>) -> (b stream<u8>, c stream<u8>) {}"
+    );
+    assert_eq!(parse("fn main() () {}"), parse("fn main() {}"));
+    assert_eq!(
+        parse("#[test]\nfn foo(n u8) -> (m u8) { m = n; }").to_string(),
+        "#[test]\nfn foo(n u8) -> (m u8) {\n    m = n;\n}"
+    );
+    assert_eq!(parse("fn main() {}").to_string(), "fn main() {}");
+}
+
+#[test]
+fn parse_examples() {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/examples");
+    let mut count = 0;
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        let program = std::fs::read_to_string(&path).unwrap();
+        let items = Parser::new(&program)
+            .file()
+            .unwrap_or_else(|errors| panic!("{}: {:?}", path.display(), errors));
+        // showing a program and parsing it again gives the same program
+        let shown: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+        assert_eq!(Parser::new(&shown.join("\n")).file().unwrap(), items);
+        count += 1;
     }
+    assert!(count > 0);
 }
 
 #[test]
 fn parse_for() {
     fn parse(input: &str) -> Stmt {
         let mut parser = Parser::new(input);
-        parser.statement()
+        parser.statement().unwrap()
     }
     assert_eq!(
         parse("for foo in bar { continue; }"),
-        Stmt::ForLoop {
-            var_name: "foo".to_string(),
-            stream: Expr::Ident("bar".to_string()),
-            stmts: vec![Stmt::ContinueStmt],
-        }
+        Stmt::from(StmtKind::ForLoop {
+            var_name: Name::new("foo"),
+            stream: Expr::from(ExprKind::Ident("bar".to_string())),
+            stmts: vec![Stmt::from(StmtKind::ContinueStmt)],
+        })
     );
     assert_eq!(
         parse("for foo in bar() {}"),
-        Stmt::ForLoop {
-            var_name: "foo".to_string(),
-            stream: Expr::FnCall {
+        Stmt::from(StmtKind::ForLoop {
+            var_name: Name::new("foo"),
+            stream: Expr::from(ExprKind::FnCall {
                 fn_name: "bar".to_string(),
                 args: vec![],
-            },
+            }),
             stmts: vec![],
-        }
+        })
     );
 }
 
@@ -605,7 +1133,7 @@ fn parse_for() {
 fn parse_file() {
     fn parse(input: &str) -> Vec<Item> {
         let mut parser = Parser::new(input);
-        parser.file()
+        parser.file().unwrap()
     }
 
     let items = parse(
@@ -618,7 +1146,7 @@ fn parse_file() {
     );
 
     assert_eq!(items.len(), 2);
-    assert!(matches!(items[1], Item::Function { .. }));
+    assert!(matches!(items[1].kind, ItemKind::Function { .. }));
 
     let items = parse(
         r#"
@@ -643,3 +1171,332 @@ fn parse_file() {
     );
     assert_eq!(items.len(), 2);
 }
+
+#[test]
+fn parse_attributes() {
+    fn parse(input: &str) -> Vec<Item> {
+        let mut parser = Parser::new(input);
+        parser.file().unwrap()
+    }
+    let items = parse(
+        r#"
+        fn main() {}
+
+        #[test]
+        fn it_works() {}
+
+        #[test] #[ignore('slow', 2)]
+        fn it_is_slow() {}
+    "#,
+    );
+    assert_eq!(items[0].attributes, vec![]);
+    assert_eq!(items[1].attributes, vec![Attribute::new("test", vec![])]);
+    assert!(items[1].has_attribute("test"));
+    assert_eq!(
+        items[2].attributes,
+        vec![
+            Attribute::new("test", vec![]),
+            Attribute::new(
+                "ignore",
+                vec![
+                    Expr::from(ExprKind::Literal(Lit::Str(b"slow".to_vec()))),
+                    Expr::from(ExprKind::Literal(Lit::Int {
+                        value: 2,
+                        suffix: None
+                    })),
+                ]
+            ),
+        ]
+    );
+    assert_eq!(items[2].attributes[1].to_string(), "#[ignore(\"slow\", 2)]");
+    assert_eq!(items[1].attributes[0].span, 31..38);
+
+    let mut parser = Parser::new("#[test]");
+    assert_eq!(
+        parser.item().unwrap_err().remove(0).to_string(),
+        "expected `fn`, found `<EOF>`"
+    );
+    let mut parser = Parser::new("#test fn foo() {}");
+    assert_eq!(
+        parser.item().unwrap_err().remove(0).to_string(),
+        "expected `[`, found `ident`"
+    );
+
+    // recovery resumes at the next item's attributes
+    let mut parser = Parser::new("fn main(x) {} #[test] fn foo() {}");
+    let (items, errors) = parser.file_with_errors();
+    assert_eq!(errors.len(), 1);
+    assert!(items[1].has_attribute("test"));
+}
+
+#[test]
+fn parse_spans() {
+    let input = "fn main() {\n    x := foo(1, bar) + 2;\n    return x;\n}";
+    let mut parser = Parser::new(input);
+    let item = parser.item().unwrap();
+    assert_eq!(item.span, 0..input.len());
+
+    let body = match item.kind {
+        ItemKind::Function { body, .. } => body,
+        _ => unreachable!(),
+    };
+    assert_eq!(&input[body[0].span.clone()], "x := foo(1, bar) + 2;");
+    assert_eq!(&input[body[1].span.clone()], "return x;");
+
+    let value = match &body[0].kind {
+        StmtKind::Declaration { var_names, value } => {
+            assert_eq!(&input[var_names[0].span.clone()], "x");
+            value
+        }
+        _ => unreachable!(),
+    };
+    assert_eq!(&input[value.span.clone()], "foo(1, bar) + 2");
+    let (lhs, rhs) = match &value.kind {
+        ExprKind::InfixOp { lhs, rhs, .. } => (lhs, rhs),
+        _ => unreachable!(),
+    };
+    assert_eq!(&input[lhs.span.clone()], "foo(1, bar)");
+    assert_eq!(&input[rhs.span.clone()], "2");
+    match &lhs.kind {
+        ExprKind::FnCall { args, .. } => {
+            assert_eq!(&input[args[1].span.clone()], "bar");
+        }
+        _ => unreachable!(),
+    }
+
+    let mut parser = Parser::new("fn foo(a stream<stream<u8>>) {}");
+    let item = parser.item().unwrap();
+    match item.kind {
+        ItemKind::Function { parameters, .. } => {
+            assert_eq!(parameters[0].span, 7..8);
+            assert_eq!(parameters[0].typ.span, 9..27);
+            assert_eq!(parameters[0].typ.generics[0].span, 16..26);
+        }
+        _ => unreachable!(),
+    }
+
+    let mut parser = Parser::new("(1 + 2) * 3 -> foo()");
+    let expr = parser.expression().unwrap();
+    assert_eq!(expr.span, 0..20);
+}
+
+#[test]
+fn parse_errors() {
+    fn parse(input: &str) -> ParseError {
+        let mut parser = Parser::new(input);
+        let mut errors = parser.file().unwrap_err();
+        assert_eq!(errors.len(), 1);
+        errors.remove(0)
+    }
+
+    let err = parse("fn main() { x := 1 }");
+    assert_eq!(err.expected, vec![Token::Semicolon]);
+    assert_eq!(err.found, Token::RightCurlyBracket);
+    assert_eq!(err.span, 19..20);
+    assert_eq!(err.to_string(), "expected `;`, found `}`");
+
+    let err = parse("fn main() { x := ; }");
+    assert_eq!(err.found, Token::Semicolon);
+    assert_eq!(err.span, 17..18);
+    assert_eq!(err.to_string(), "expected expression, found `;`");
+
+    let err = parse("fn 1() {}");
+    assert_eq!(err.expected, vec![Token::Ident]);
+    assert_eq!(err.span, 3..4);
+
+    let err = parse("fn main(a u8, 2 u8) {}");
+    assert_eq!(err.expected, vec![Token::Ident]);
+    assert_eq!(err.span, 14..15);
+
+    let err = parse("fn main() out u8 {}");
+    assert_eq!(
+        err.expected,
+        vec![Token::RightArrow, Token::LeftParen, Token::LeftCurlyBracket]
+    );
+    assert_eq!(err.found, Token::Ident);
+
+    let err = parse("fn main() {} main");
+    assert_eq!(err.expected, vec![Token::KeywordFn]);
+    assert_eq!(err.found, Token::Ident);
+
+    let err = parse("fn main() { foo bar; }");
+    assert_eq!(err.to_string(), "expected `;`, found `ident`");
+    assert_eq!(err.span, 16..19);
+
+    let err = parse("fn main() { 1 -> 2; }");
+    assert_eq!(
+        err.to_string(),
+        "expected a function call, a stream or a tuple of streams after `->`, found `number`"
+    );
+    assert_eq!(err.span, 17..18);
+
+    let err = parse("fn main() { foo(1; }");
+    assert_eq!(err.expected, vec![Token::Comma, Token::RightParen]);
+
+    let err = parse("fn main() {");
+    assert_eq!(err.found, Token::EOF);
+    assert_eq!(err.span, 11..11);
+}
+
+#[test]
+fn parse_recovery() {
+    fn parse(input: &str) -> (Vec<Item>, Vec<ParseError>) {
+        let mut parser = Parser::new(input);
+        parser.file_with_errors()
+    }
+
+    // missing semicolons at the end of a line or before `}` keep the statement
+    let (items, errors) = parse(
+        r#"
+        fn parse(input stream<u8>) -> (out stream<u8>) {
+            x := 1
+            if x == 2 {
+                return input
+            }
+            return input;
+        }
+    "#,
+    );
+    assert_eq!(errors.len(), 2);
+    assert!(errors
+        .iter()
+        .all(|err| err.expected == vec![Token::Semicolon]));
+    assert_eq!(errors[0].found, Token::KeywordIf);
+    assert_eq!(errors[1].found, Token::RightCurlyBracket);
+    match &items[0].kind {
+        ItemKind::Function { body, .. } => {
+            assert_eq!(body.len(), 3);
+            assert!(matches!(body[0].kind, StmtKind::Declaration { .. }));
+            assert!(matches!(body[1].kind, StmtKind::IfStmt { .. }));
+        }
+        _ => unreachable!(),
+    }
+
+    // broken statements are replaced by error nodes
+    let input = r#"
+        fn main() {
+            x := ;
+            foo bar baz;
+            if x + { y := 1; }
+            z := 2;
+        }
+        fn other() {
+            1 + ;
+        }
+    "#;
+    let (items, errors) = parse(input);
+    assert_eq!(errors.len(), 4);
+    assert_eq!(errors[0].to_string(), "expected expression, found `;`");
+    assert_eq!(errors[1].to_string(), "expected `;`, found `ident`");
+    assert_eq!(errors[2].to_string(), "expected expression, found `{`");
+    assert_eq!(errors[3].to_string(), "expected expression, found `;`");
+    assert_eq!(items.len(), 2);
+    match &items[0].kind {
+        ItemKind::Function { body, .. } => {
+            assert_eq!(body.len(), 4);
+            assert_eq!(body[0].kind, StmtKind::Error);
+            assert_eq!(&input[body[0].span.clone()], "x := ;");
+            assert_eq!(body[1].kind, StmtKind::Error);
+            assert_eq!(&input[body[1].span.clone()], "foo bar baz;");
+            assert_eq!(body[2].kind, StmtKind::Error);
+            assert_eq!(&input[body[2].span.clone()], "if x + { y := 1; }");
+            assert!(matches!(body[3].kind, StmtKind::Declaration { .. }));
+        }
+        _ => unreachable!(),
+    }
+    assert!(matches!(items[1].kind, ItemKind::Function { .. }));
+
+    // broken items are skipped up to the next `fn`
+    let (items, errors) = parse("fn (a u8) { x := 1; } fn main() { x := 1 + ; } fn ok() {}");
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].expected, vec![Token::Ident]);
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[0].kind, ItemKind::Error);
+    assert!(matches!(items[1].kind, ItemKind::Function { .. }));
+    assert!(matches!(items[2].kind, ItemKind::Function { .. }));
+
+    // an unclosed block ends at the next `fn`
+    let (items, errors) = parse("fn main() { x := 1;\nfn ok() {}");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].expected, vec![Token::RightCurlyBracket]);
+    assert_eq!(errors[0].found, Token::KeywordFn);
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0].kind, ItemKind::Error);
+    assert!(matches!(items[1].kind, ItemKind::Function { .. }));
+
+    // `file` and `statement` still fail when anything was recovered from
+    assert_eq!(
+        Parser::new("fn main() { x := ; }")
+            .file()
+            .unwrap_err()
+            .len(),
+        1
+    );
+    assert!(Parser::new("{ x := ; }").statement().is_err());
+
+    // and report every error they recovered from, not only the first
+    let errors = Parser::new("{ x := ; y := 1 + ; }")
+        .statement()
+        .unwrap_err();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].span, 7..8);
+    assert_eq!(errors[1].span, 18..19);
+    let errors = Parser::new("x := 1 $+ 2 $").statement().unwrap_err();
+    assert_eq!(errors.len(), 3);
+    assert_eq!(errors[0].to_string(), "unrecognized token `$`");
+    assert_eq!(errors[1].to_string(), "unrecognized token `$`");
+    assert_eq!(errors[2].found, Token::EOF);
+}
+
+#[test]
+fn parse_while() {
+    fn parse(input: &str) -> Stmt {
+        let mut parser = Parser::new(input);
+        parser.statement().unwrap()
+    }
+    assert_eq!(
+        parse("while foo { break; }"),
+        Stmt::from(StmtKind::WhileLoop {
+            condition: Expr::from(ExprKind::Ident("foo".to_string())),
+            stmts: vec![Stmt::from(StmtKind::BreakStmt)],
+        })
+    );
+
+    let stmt = parse(
+        r#"
+        while i < len && !done {
+            a.next() -> out;
+            if done {
+                break;
+            } else {
+                continue;
+            }
+        }
+    "#,
+    );
+    match &stmt.kind {
+        StmtKind::WhileLoop { condition, stmts } => {
+            assert_eq!(condition.to_string(), "((i < len) && (!done))");
+            assert_eq!(stmts.len(), 2);
+        }
+        _ => unreachable!(),
+    }
+    assert_eq!(
+        stmt.to_string(),
+        r#"while ((i < len) && (!done)) {
+    a.next() -> out;
+    if done {
+        break;
+    } else {
+        continue;
+    }
+}"#
+    );
+
+    assert_eq!(
+        parse("for x in xs { while x < 2 {} }").to_string(),
+        "for x in xs {\n    while (x < 2) {}\n}"
+    );
+    assert!(Parser::new("while {}").statement().is_err());
+    assert!(Parser::new("break").statement().is_err());
+}
@@ -0,0 +1,95 @@
+use burn::testing::{run_tests, Outcome};
+use std::fs;
+
+const PROGRAM: &str = r#"
+fn double(input stream<u8>) -> (out stream<u8>) {
+    for b in input {
+        b -> out;
+        b -> out;
+    }
+}
+
+fn main() {
+    SOURCES::stdin() -> double() -> SINKS::stdout();
+}
+
+#[test]
+fn it_doubles() {
+    assert double('ab') == 'aabb';
+}
+
+#[test]
+fn it_reads_stubbed_sources() {
+    input := SOURCES::stdin();
+    assert input.eof();
+    double(input) -> SINKS::stdout();
+}
+
+#[test]
+fn it_fails() {
+    assert double('ab') == 'abab';
+}
+
+#[test]
+#[ignore]
+fn it_is_ignored() {
+    assert false;
+}
+"#;
+
+#[test]
+fn run_all_tests() {
+    let report = run_tests(PROGRAM, "").unwrap();
+    let names: Vec<&str> = report.results.iter().map(|(n, _)| n.as_str()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "it_doubles",
+            "it_reads_stubbed_sources",
+            "it_fails",
+            "it_is_ignored"
+        ]
+    );
+    assert_eq!(report.results[0].1, Outcome::Passed);
+    assert_eq!(report.results[1].1, Outcome::Passed);
+    assert!(matches!(report.results[2].1, Outcome::Failed(_)));
+    assert_eq!(report.results[3].1, Outcome::Ignored);
+    assert!(!report.passed());
+    assert_eq!(report.filtered_out, 0);
+}
+
+#[test]
+fn run_filtered_tests() {
+    let report = run_tests(PROGRAM, "doubles").unwrap();
+    assert_eq!(
+        report.results,
+        vec![("it_doubles".to_string(), Outcome::Passed)]
+    );
+    assert_eq!(report.filtered_out, 3);
+    assert!(report.passed());
+}
+
+#[test]
+fn run_tests_needs_valid_program() {
+    let diagnostics = run_tests("#[test] fn foo() { x = 1; }", "").unwrap_err();
+    assert_eq!(diagnostics[0].message, "cannot find `x` in this scope");
+}
+
+#[test]
+fn run_tests_without_main() {
+    let report = run_tests("#[test] fn it_adds() { assert 1 + 1 == 2; }", "").unwrap();
+    assert_eq!(
+        report.results,
+        vec![("it_adds".to_string(), Outcome::Passed)]
+    );
+}
+
+#[test]
+fn run_example_tests() {
+    for example in &["examples/rot13.burn", "examples/mix.burn"] {
+        let program = fs::read_to_string(example).unwrap();
+        let report = run_tests(&program, "").unwrap();
+        assert_eq!(report.results.len(), 1);
+        assert!(report.passed(), "{}: {:?}", example, report);
+    }
+}